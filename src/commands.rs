@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use process_memory::{
+    Backend, Error, Filter, FreezeMethod, Process, ProcessQuery, Result, VirtMemoryPage,
+};
+use regex::Regex;
+
+pub mod diff;
+pub mod dump;
+pub mod maps;
+pub mod read;
+pub mod restore;
+pub mod scan;
+pub mod search;
+pub mod write;
+
+/// which process to use, all given conditions except for --pid can be combined
+#[derive(Args)]
+#[group(required = true, multiple = true)]
+pub struct TargetArgs {
+    /// PID of the process
+    #[arg(short, long, conflicts_with_all = ["name", "cmdline", "exe", "ppid"])]
+    pid: Option<u32>,
+    /// name of the process as shown in /proc/PID/comm
+    #[arg(short, long)]
+    name: Option<String>,
+    /// regular expression matched against the command line, the arguments are joined by spaces
+    #[arg(long, value_name = "REGEX")]
+    cmdline: Option<Regex>,
+    /// path of the executable of the process
+    #[arg(long, value_name = "PATH")]
+    exe: Option<PathBuf>,
+    /// PID of the parent of the process
+    #[arg(long, value_name = "PID")]
+    ppid: Option<u32>,
+}
+
+impl TargetArgs {
+    /// the selected process, fails if the conditions match no or multiple processes
+    pub fn resolve(&self) -> Result<Process> {
+        match self.pid {
+            Some(pid) => Process::new(pid),
+            None => self.query().find_one(),
+        }
+    }
+
+    /// all processes matching the conditions, fails if there is none
+    pub fn resolve_all(&self) -> Result<Vec<Process>> {
+        if let Some(pid) = self.pid {
+            return Ok(vec![Process::new(pid)?]);
+        }
+        let query = self.query();
+        let processes = query.find()?;
+        if processes.is_empty() {
+            return Err(Error::NoMatchingProcess(query.to_string()));
+        }
+        Ok(processes)
+    }
+
+    fn query(&self) -> ProcessQuery {
+        ProcessQuery {
+            name: self.name.clone(),
+            cmdline: self.cmdline.clone(),
+            exe: self.exe.clone(),
+            ppid: self.ppid,
+        }
+    }
+}
+
+/// which pages to read and how to read them
+#[derive(Args)]
+pub struct SelectArgs {
+    /// only use pages matching FILTER, a comma separated list of conditions that all have to match.
+    /// If the option is given multiple times pages matching any of the filters are used.
+    /// Conditions: perms=MASK (e.g. r-x, rw?, r--p), path=GLOB, anon, addr=FROM-TO,
+    /// min-size=SIZE, max-size=SIZE, min-STAT=SIZE, max-STAT=SIZE (STAT is rss, pss, shared-clean,
+    /// shared-dirty, private-clean, private-dirty, swap or anon-huge-pages), dirty, swapped,
+    /// flag=VMFLAG, !CONDITION
+    #[arg(short, long = "filter", value_name = "FILTER")]
+    filters: Vec<Filter>,
+    /// how to read the memory: vm-readv (process_vm_readv), proc-mem (/proc/PID/mem)
+    /// or auto (vm-readv with a fallback to proc-mem)
+    #[arg(long, default_value = "auto")]
+    pub backend: Backend,
+    /// stop the process while it is read for a consistent snapshot.
+    /// METHOD is signal (SIGSTOP/SIGCONT) or ptrace (interrupt every thread)
+    #[arg(
+        long,
+        value_name = "METHOD",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "signal"
+    )]
+    pub freeze: Option<FreezeMethod>,
+}
+
+impl SelectArgs {
+    /// the filter given by the user or `default` if there is none
+    pub fn filter(&self, default: Filter) -> Filter {
+        if self.filters.is_empty() {
+            default
+        } else {
+            Filter::any(self.filters.clone())
+        }
+    }
+
+    /// the pages of `process` matching the filter, `default` is used if no filter was given
+    pub fn pages(&self, process: &Process, default: Filter) -> Result<Vec<VirtMemoryPage>> {
+        let filter = self.filter(default);
+        let pages = if filter.needs_smaps() {
+            process.smaps()?
+        } else {
+            process.maps()?
+        };
+        Ok(pages.into_iter().filter(|m| filter.matches(m)).collect())
+    }
+}
+
+/// parse a hex address with an optional `0x` prefix
+pub fn parse_address(s: &str) -> std::result::Result<u64, String> {
+    u64::from_str_radix(s.trim_start_matches("0x"), 16)
+        .map_err(|_| format!("invalid hex address '{s}'"))
+}
+
+/// the pathname of a page for humans, `[anonymous]` if it has none
+pub fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "[anonymous]"
+    } else {
+        path
+    }
+}
+
+/// a size in bytes for humans like `ls -h`, e.g. `512`, `4.0K`, `132K` or `1.5M`
+pub fn human_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        format!("{value:.1}{}", UNITS[unit])
+    } else {
+        format!("{value:.0}{}", UNITS[unit])
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use process_memory::{diff, Backend, Error, Filter, Result, Snapshot};
+
+use super::{display_path, TargetArgs};
+use crate::Outcome;
+
+/// Compare two dumps or a dump and the live process
+///
+/// Regions are matched by their address range. Added and removed mappings, changed permissions
+/// and the changed byte ranges of each region are reported.
+#[derive(Args)]
+#[command(mut_group("TargetArgs", |group| group.required(false)))]
+pub struct DiffArgs {
+    /// dump directory, core file or tar archive
+    old: PathBuf,
+    /// dump directory, core file or tar archive, the live process given by --pid, --name, ... if omitted
+    #[arg(conflicts_with = "TargetArgs")]
+    new: Option<PathBuf>,
+    /// the live process
+    #[command(flatten)]
+    target: Option<TargetArgs>,
+    /// only compare pages matching FILTER, see the help of dump
+    #[arg(short, long = "filter", value_name = "FILTER")]
+    filters: Vec<Filter>,
+    /// how to read the memory of the live process
+    #[arg(long, default_value = "auto")]
+    backend: Backend,
+    /// print the differences as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub fn run(args: DiffArgs) -> Result<Outcome> {
+    let mut old = Snapshot::open(&args.old)?;
+    let mut new = match (&args.new, &args.target) {
+        (Some(path), _) => Snapshot::open(path)?,
+        (None, Some(target)) => Snapshot::live(&target.resolve()?, args.backend)?,
+        (None, None) => {
+            return Err(Error::InvalidArgument(
+                "either a second dump or the live process is required".to_string(),
+            ))
+        }
+    };
+    let diff = diff(&mut old, &mut new, &Filter::any(args.filters))?;
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&diff).map_err(|err| Error::Output(err.into()))?
+        );
+        return Ok(Outcome::Complete);
+    }
+
+    for mapping in &diff.removed {
+        println!(
+            "- {:x}-{:x} {} {}",
+            mapping.start,
+            mapping.end,
+            mapping.perms,
+            display_path(&mapping.path)
+        );
+    }
+    for mapping in &diff.added {
+        println!(
+            "+ {:x}-{:x} {} {}",
+            mapping.start,
+            mapping.end,
+            mapping.perms,
+            display_path(&mapping.path)
+        );
+    }
+    for region in &diff.changed {
+        let perms = if region.old_perms == region.new_perms {
+            region.new_perms.clone()
+        } else {
+            format!("{} -> {}", region.old_perms, region.new_perms)
+        };
+        println!(
+            "~ {:x}-{:x} {perms} {}: {} bytes changed in {} ranges{}",
+            region.start,
+            region.end,
+            display_path(&region.path),
+            region.changed_bytes(),
+            region.changes.len(),
+            if region.unreadable {
+                ", partially unreadable"
+            } else {
+                ""
+            }
+        );
+        for change in &region.changes {
+            println!(
+                "    {:#x}-{:#x} ({} bytes)",
+                change.start,
+                change.end,
+                change.end - change.start
+            );
+        }
+    }
+    Ok(Outcome::Complete)
+}
@@ -0,0 +1,314 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::{Args, ValueEnum};
+use process_memory::{
+    dump_dir, dump_tar, write_core, Compression, DumpedPage, Error, Filter, Index, Manifest,
+    Process, ProcessMemory, Result, Sink, SparseFile, TarWriter, VirtMemoryPage, ZeroFill,
+    INDEX_FILE_NAME, MANIFEST_FILE_NAME,
+};
+
+use super::{SelectArgs, TargetArgs};
+use crate::Outcome;
+
+/// Dump the memory of a process
+///
+/// By default all readable and writable pages are dumped.
+#[derive(Args)]
+pub struct DumpArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+    #[command(flatten)]
+    select: SelectArgs,
+    /// dir: write a file per page into a directory, core: write an ELF core file that can be opened with gdb,
+    /// tar: write the pages and the manifest into a single tar archive
+    #[arg(long, value_enum, default_value_t = Format::Dir)]
+    format: Format,
+    /// output directory or file, - writes a tar archive to stdout
+    /// [default: memory for dir, core.PID for core, memory.tar for tar]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// compress every file of a dump directory, the core file or the tar archive: zstd, gzip or none
+    #[arg(long, default_value = "none")]
+    compress: Compression,
+    /// also dump the stack of every thread if it isn't selected by a filter
+    #[arg(long)]
+    stacks: bool,
+    /// dump every matching process into the output directory instead of requiring a unique match
+    #[arg(long)]
+    all: bool,
+    /// also dump all descendants of the process into the output directory
+    #[arg(long)]
+    tree: bool,
+    /// dump all processes of a cgroup and the cgroups below it into the output directory,
+    /// e.g. /system.slice/nginx.service
+    #[arg(
+        long,
+        value_name = "CGROUP",
+        group = "TargetArgs",
+        conflicts_with_all = ["pid", "name", "cmdline", "exe", "ppid", "all", "tree"]
+    )]
+    cgroup: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Dir,
+    Core,
+    Tar,
+}
+
+impl Format {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Dir => "dir",
+            Self::Core => "core",
+            Self::Tar => "tar",
+        }
+    }
+
+    /// the name of the dump of the process `pid` inside of the output directory if multiple processes are dumped
+    fn name_in_dir(&self, pid: u32, compression: Compression) -> String {
+        match self {
+            Self::Dir => pid.to_string(),
+            Self::Core => format!("core.{pid}{}", compression.extension()),
+            Self::Tar => format!("{pid}.tar{}", compression.extension()),
+        }
+    }
+}
+
+pub fn run(args: DumpArgs) -> Result<Outcome> {
+    if args.output.as_deref() == Some(Path::new("-")) && !matches!(args.format, Format::Tar) {
+        return Err(Error::InvalidArgument(
+            "only a tar archive can be written to stdout".to_string(),
+        ));
+    }
+    let processes = if let Some(cgroup) = &args.cgroup {
+        let processes = Process::in_cgroup(cgroup)?;
+        if processes.is_empty() {
+            return Err(Error::NoMatchingProcess(format!(
+                "cgroup {}",
+                cgroup.display()
+            )));
+        }
+        processes
+    } else if args.tree || args.all {
+        let roots = if args.all {
+            args.target.resolve_all()?
+        } else {
+            vec![args.target.resolve()?]
+        };
+        if args.tree {
+            let mut processes = Vec::<Process>::new();
+            for root in roots {
+                for process in root.tree()? {
+                    if !processes.iter().any(|p| p.pid() == process.pid()) {
+                        processes.push(process);
+                    }
+                }
+            }
+            processes
+        } else {
+            roots
+        }
+    } else {
+        let process = args.target.resolve()?;
+        return dump(&process, &args, args.output.clone());
+    };
+
+    // don't dump ourselves if we are part of the tree or cgroup
+    let processes = processes
+        .into_iter()
+        .filter(|p| p.pid() != std::process::id())
+        .collect::<Vec<_>>();
+
+    // every process gets its own dump inside of the output directory
+    let output_dir = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("memory"));
+    if output_dir == Path::new("-") {
+        return Err(Error::InvalidArgument(
+            "multiple processes can't be written to stdout".to_string(),
+        ));
+    }
+    create_dir_all(&output_dir).map_err(Error::Output)?;
+    let mut index = Index::new(&processes);
+    let mut outcome = Outcome::Complete;
+    for (process, entry) in processes.iter().zip(&mut index.processes) {
+        let name = args.format.name_in_dir(process.pid(), args.compress);
+        println!("process {} ({}) -> {name}", process.pid(), entry.comm);
+        match dump(process, &args, Some(output_dir.join(&name))) {
+            Ok(result) => {
+                entry.dump = Some(PathBuf::from(name));
+                if let Outcome::Partial = result {
+                    outcome = Outcome::Partial;
+                }
+            }
+            // a process that exited or can't be accessed should not abort the others
+            Err(err) => {
+                eprintln!("process {}: {err}", process.pid());
+                entry.error = Some(err.to_string());
+                outcome = Outcome::Partial;
+            }
+        }
+    }
+    index.write(&output_dir.join(INDEX_FILE_NAME))?;
+    Ok(outcome)
+}
+
+/// dump `process` to `output` or the default output of the format
+fn dump(process: &Process, args: &DumpArgs, output: Option<PathBuf>) -> Result<Outcome> {
+    // resumed when dropped at the end of the dump or on error
+    let frozen = args
+        .select
+        .freeze
+        .map(|method| process.freeze(method))
+        .transpose()?;
+
+    let threads = process.capture_threads()?;
+    // smaps instead of maps to record the memory usage in the manifest
+    let pages = process.smaps()?;
+    let filter = args.select.filter(Filter::readable_writable());
+    let memory_parts = pages
+        .iter()
+        .filter(|page| {
+            filter.matches(page)
+                || args.stacks && threads.iter().any(|t| t.stack(&pages) == Some(page))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut pmemory = process.memory_with(args.select.backend)?;
+
+    let to_stdout = output.as_deref() == Some(Path::new("-"));
+    let manifest = |dumped: &[DumpedPage]| {
+        Manifest::new(
+            process,
+            args.format.name(),
+            args.compress,
+            dumped,
+            &threads,
+            &pages,
+        )
+    };
+    let dumped = match args.format {
+        Format::Dir => {
+            let output_dir = output.unwrap_or_else(|| PathBuf::from("memory"));
+            let dumped = dump_dir(&mut pmemory, memory_parts, &output_dir, args.compress)?;
+            manifest(&dumped)?.write(&output_dir.join(MANIFEST_FILE_NAME))?;
+            dumped
+        }
+        Format::Core => {
+            let path = output.unwrap_or_else(|| {
+                PathBuf::from(format!(
+                    "core.{}{}",
+                    process.pid(),
+                    args.compress.extension()
+                ))
+            });
+            let file = SparseFile::new(File::create(&path).map_err(Error::Output)?);
+            let mut out = BufWriter::new(args.compress.encoder(file).map_err(Error::Output)?);
+            let dumped = write_core(process, &threads, &mut pmemory, memory_parts, &mut out)?;
+            out.into_inner()
+                .map_err(|err| Error::Output(err.into_error()))?
+                .finish()
+                .map_err(Error::Output)?;
+            let mut manifest_path = path.into_os_string();
+            manifest_path.push(".json");
+            manifest(&dumped)?.write(Path::new(&manifest_path))?;
+            dumped
+        }
+        Format::Tar if to_stdout => tar(
+            ZeroFill(io::stdout().lock()),
+            args.compress,
+            &mut pmemory,
+            memory_parts,
+            manifest,
+        )?,
+        Format::Tar => {
+            let path = output.unwrap_or_else(|| {
+                PathBuf::from(format!("memory.tar{}", args.compress.extension()))
+            });
+            let file = SparseFile::new(File::create(&path).map_err(Error::Output)?);
+            tar(file, args.compress, &mut pmemory, memory_parts, manifest)?
+        }
+    };
+    if let Some(frozen) = frozen {
+        frozen.resume()?;
+    }
+
+    // keep stdout clean if the archive is written to it
+    let report = |line: String| {
+        if to_stdout {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    };
+    for thread in &threads {
+        let name = format!(
+            "thread {} {} {}",
+            thread.tid, thread.name, thread.stat.state
+        );
+        if let Some(registers) = &thread.registers {
+            let stack = thread.stack(&pages).map_or("unmapped".to_string(), |p| {
+                format!("{:#x}-{:#x}", p.from, p.to)
+            });
+            report(format!(
+                "{name}: pc {:#x}, sp {:#x}, stack {stack}",
+                registers.instruction_pointer(),
+                registers.stack_pointer()
+            ));
+        } else if let Some(err) = &thread.error {
+            eprintln!("{name}: registers unreadable: {err}");
+        }
+    }
+
+    let mut outcome = Outcome::Complete;
+    for part in dumped {
+        match part.error {
+            None => report(format!("read {}", part.page)),
+            // a single unreadable page should not abort the whole dump
+            Some(err) => {
+                eprintln!(
+                    "partial {:#x}-{:#x}: {} of {} bytes unreadable: {err}",
+                    part.page.from,
+                    part.page.to,
+                    part.page.size() - part.read,
+                    part.page.size()
+                );
+                outcome = Outcome::Partial;
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+/// stream `pages` and the manifest returned by `manifest` into a tar archive written to `out`
+fn tar<S: Sink>(
+    out: S,
+    compression: Compression,
+    memory: &mut ProcessMemory,
+    pages: Vec<VirtMemoryPage>,
+    manifest: impl FnOnce(&[DumpedPage]) -> Result<Manifest>,
+) -> Result<Vec<DumpedPage>> {
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let out = BufWriter::new(compression.encoder(out).map_err(Error::Output)?);
+    let mut tar = TarWriter::new(out, mtime);
+    let dumped = dump_tar(memory, pages, &mut tar)?;
+    tar.append(MANIFEST_FILE_NAME, &manifest(&dumped)?.to_json())
+        .map_err(Error::Output)?;
+    tar.finish()
+        .map_err(Error::Output)?
+        .into_inner()
+        .map_err(|err| Error::Output(err.into_error()))?
+        .finish()
+        .map_err(Error::Output)?;
+    Ok(dumped)
+}
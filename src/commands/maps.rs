@@ -0,0 +1,207 @@
+use std::io::{self, Write};
+
+use clap::{Args, ValueEnum};
+use process_memory::{group_by, Error, Filter, Result, VirtMemoryPage};
+use serde::Serialize;
+
+use super::{display_path, human_size, TargetArgs};
+use crate::Outcome;
+
+/// List the memory mappings of a process
+///
+/// Prints the mappings from /proc/PID/smaps as a table with their size and resident memory.
+#[derive(Args)]
+pub struct MapsArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+    /// only list pages matching FILTER, see the help of dump
+    #[arg(short, long = "filter", value_name = "FILTER")]
+    filters: Vec<Filter>,
+    /// order of the rows
+    #[arg(long, value_enum, default_value_t = SortKey::Address)]
+    sort: SortKey,
+    /// reverse the order of the rows
+    #[arg(short, long)]
+    reverse: bool,
+    /// print the number of mappings, size and RSS per pathname instead of every mapping
+    #[arg(long)]
+    totals: bool,
+    /// print the rows as JSON
+    #[arg(long, conflicts_with = "csv")]
+    json: bool,
+    /// print the rows as CSV with sizes in bytes
+    #[arg(long)]
+    csv: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SortKey {
+    Address,
+    Size,
+    Rss,
+    Path,
+}
+
+/// a row of the table: a single mapping or all mappings of a pathname
+#[derive(Serialize)]
+struct Row {
+    start: String, // hex, the lowest start address for totals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<String>, // hex, only for single mappings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    perms: Option<String>, // only for single mappings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mappings: Option<usize>, // only for totals
+    size: u64,
+    rss: u64,
+    path: String,
+    #[serde(skip)]
+    address: u64, // start as number for sorting
+}
+
+impl Row {
+    fn mapping(page: &VirtMemoryPage) -> Self {
+        Self {
+            start: format!("{:#x}", page.from),
+            end: Some(format!("{:#x}", page.to)),
+            perms: Some(page.perms()),
+            mappings: None,
+            size: page.size(),
+            rss: rss(page),
+            path: page.file_path.clone(),
+            address: page.from,
+        }
+    }
+
+    fn total(path: String, pages: &[VirtMemoryPage]) -> Self {
+        let address = pages.iter().map(|p| p.from).min().unwrap_or_default();
+        Self {
+            start: format!("{address:#x}"),
+            end: None,
+            perms: None,
+            mappings: Some(pages.len()),
+            size: pages.iter().map(VirtMemoryPage::size).sum(),
+            rss: pages.iter().map(rss).sum(),
+            path,
+            address,
+        }
+    }
+}
+
+fn rss(page: &VirtMemoryPage) -> u64 {
+    page.stats.as_ref().map_or(0, |stats| stats.rss)
+}
+
+pub fn run(args: MapsArgs) -> Result<Outcome> {
+    let process = args.target.resolve()?;
+    let filter = Filter::any(args.filters);
+    let pages = process
+        .smaps()?
+        .into_iter()
+        .filter(|page| filter.matches(page))
+        .collect::<Vec<_>>();
+
+    let mut rows = if args.totals {
+        group_by(pages, |page| page.file_path.clone())
+            .into_iter()
+            .map(|(path, pages)| Row::total(path, &pages))
+            .collect::<Vec<_>>()
+    } else {
+        pages.iter().map(Row::mapping).collect()
+    };
+    match args.sort {
+        SortKey::Address => rows.sort_by_key(|row| row.address),
+        // largest first, the address keeps the order stable
+        SortKey::Size => rows.sort_by_key(|row| (u64::MAX - row.size, row.address)),
+        SortKey::Rss => rows.sort_by_key(|row| (u64::MAX - row.rss, row.address)),
+        SortKey::Path => rows.sort_by(|a, b| (&a.path, a.address).cmp(&(&b.path, b.address))),
+    }
+    if args.reverse {
+        rows.reverse();
+    }
+
+    let mut out = io::stdout().lock();
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, &rows).map_err(|err| Error::Output(err.into()))?;
+        writeln!(out).map_err(Error::Output)?;
+    } else if args.csv {
+        print_csv(&mut out, &rows, args.totals).map_err(Error::Output)?;
+    } else {
+        print_table(&mut out, &rows, args.totals).map_err(Error::Output)?;
+    }
+    Ok(Outcome::Complete)
+}
+
+fn print_table(out: &mut impl Write, rows: &[Row], totals: bool) -> io::Result<()> {
+    if totals {
+        writeln!(out, "{:>8} {:>8} {:>8} PATH", "MAPPINGS", "SIZE", "RSS")?;
+        for row in rows {
+            writeln!(
+                out,
+                "{:>8} {:>8} {:>8} {}",
+                row.mappings.unwrap_or_default(),
+                human_size(row.size),
+                human_size(row.rss),
+                display_path(&row.path)
+            )?;
+        }
+        writeln!(
+            out,
+            "{:>8} {:>8} {:>8} total",
+            rows.iter().filter_map(|row| row.mappings).sum::<usize>(),
+            human_size(rows.iter().map(|row| row.size).sum()),
+            human_size(rows.iter().map(|row| row.rss).sum())
+        )
+    } else {
+        writeln!(
+            out,
+            "{:>18} {:>18} {:>8} PERMS {:>8} PATH",
+            "START", "END", "SIZE", "RSS"
+        )?;
+        for row in rows {
+            writeln!(
+                out,
+                "{:>18} {:>18} {:>8} {:<5} {:>8} {}",
+                row.start,
+                row.end.as_deref().unwrap_or_default(),
+                human_size(row.size),
+                row.perms.as_deref().unwrap_or_default(),
+                human_size(row.rss),
+                display_path(&row.path)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn print_csv(out: &mut impl Write, rows: &[Row], totals: bool) -> io::Result<()> {
+    if totals {
+        writeln!(out, "start,mappings,size,rss,path")?;
+    } else {
+        writeln!(out, "start,end,perms,size,rss,path")?;
+    }
+    for row in rows {
+        if totals {
+            write!(out, "{},{},", row.start, row.mappings.unwrap_or_default())?;
+        } else {
+            write!(
+                out,
+                "{},{},{},",
+                row.start,
+                row.end.as_deref().unwrap_or_default(),
+                row.perms.as_deref().unwrap_or_default()
+            )?;
+        }
+        writeln!(out, "{},{},{}", row.size, row.rss, csv_field(&row.path))?;
+    }
+    Ok(())
+}
+
+/// quote `field` if it contains a comma, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
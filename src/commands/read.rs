@@ -0,0 +1,299 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use clap::{Args, ValueEnum};
+use process_memory::{Backend, Error, Process, Result, ValueType, VirtMemoryPage};
+
+use super::{display_path, parse_address, TargetArgs};
+use crate::Outcome;
+
+/// number of bytes read if no length is given for a hexdump or raw output
+const DEFAULT_LENGTH: u64 = 64;
+/// maximum number of bytes read for a C string if no length is given
+const MAX_CSTRING_LENGTH: u64 = 4096;
+/// bytes per line of the hexdump
+const HEXDUMP_WIDTH: usize = 16;
+
+/// Read a range of memory of a process
+///
+/// Prints a hexdump by default. The range has to be mapped and readable.
+#[derive(Args)]
+pub struct ReadArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+    /// what to read: ADDR[:LEN] with a hex address, or NAME[+OFFSET][:LEN] where NAME is a symbol
+    /// of a mapped ELF file or the pathname or file name of a mapping, e.g. malloc, libc.so.6+0x1000
+    /// or [heap]+0x10. LEN and OFFSET are decimal or hex with 0x
+    location: String,
+    /// write the bytes to stdout instead of a hexdump
+    #[arg(long, conflicts_with = "value_type")]
+    raw: bool,
+    /// interpret the bytes as values of TYPE: u8, u16, u32, u64, i8, i16, i32, i64, f32, f64,
+    /// pointer or cstring. LEN defaults to a single value
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    value_type: Option<ReadType>,
+    /// byte order of the values [default: the byte order of this machine]
+    #[arg(long, value_enum, requires = "value_type")]
+    endian: Option<Endian>,
+    /// how to read the memory: vm-readv, proc-mem or auto
+    #[arg(long, default_value = "auto")]
+    backend: Backend,
+}
+
+#[derive(Clone, Copy)]
+enum ReadType {
+    Value(ValueType),
+    Pointer,
+    CString, // bytes up to the first NUL
+}
+
+impl FromStr for ReadType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "pointer" => Ok(Self::Pointer),
+            "cstring" => Ok(Self::CString),
+            "string" => Err(Error::InvalidArgument(
+                "use cstring to read a string".to_string(),
+            )),
+            _ => Ok(Self::Value(s.parse()?)),
+        }
+    }
+}
+
+impl ReadType {
+    /// size of a value in bytes, `None` for C strings
+    fn size(self) -> Option<u64> {
+        match self {
+            Self::Value(value_type) => value_type.size().map(|size| size as u64),
+            Self::Pointer => Some(size_of::<usize>() as u64),
+            Self::CString => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    fn native() -> Self {
+        if cfg!(target_endian = "big") {
+            Self::Big
+        } else {
+            Self::Little
+        }
+    }
+}
+
+pub fn run(args: ReadArgs) -> Result<Outcome> {
+    let process = args.target.resolve()?;
+    let pages = process.maps()?;
+    let (address, length) = locate(&process, &pages, &args.location)?;
+    let length = match (length, args.value_type) {
+        (Some(length), _) => length,
+        (None, Some(ReadType::CString)) => MAX_CSTRING_LENGTH,
+        (None, Some(value_type)) => value_type.size().unwrap_or(DEFAULT_LENGTH),
+        (None, None) => DEFAULT_LENGTH,
+    };
+    if length == 0 {
+        return Err(Error::InvalidArgument(
+            "the length must not be 0".to_string(),
+        ));
+    }
+    if let Some(size) = args.value_type.and_then(ReadType::size) {
+        if length % size != 0 {
+            return Err(Error::InvalidArgument(format!(
+                "the length {length} is not a multiple of the value size {size}"
+            )));
+        }
+    }
+
+    let readable = readable_length(&pages, address, length);
+    if readable == 0 {
+        return Err(Error::InvalidArgument(format!(
+            "{address:#x} is not in a readable mapping"
+        )));
+    }
+    let length = match args.value_type {
+        // a C string ends at the end of the readable range at the latest
+        Some(ReadType::CString) => readable,
+        _ if readable < length => {
+            return Err(Error::InvalidArgument(format!(
+                "{:#x}-{:#x} is not readable completely, only {readable} bytes are mapped",
+                address,
+                address.saturating_add(length)
+            )))
+        }
+        _ => length,
+    };
+    let mut buf = vec![0; length as usize];
+    process
+        .memory_with(args.backend)?
+        .read_at(address, &mut buf)?;
+
+    let mut out = io::stdout().lock();
+    match args.value_type {
+        _ if args.raw => out.write_all(&buf),
+        None => hexdump(&mut out, address, &buf),
+        Some(value_type) => print_values(
+            &mut out,
+            &pages,
+            address,
+            &buf,
+            value_type,
+            args.endian.unwrap_or_else(Endian::native),
+        ),
+    }
+    .map_err(Error::Output)?;
+    Ok(Outcome::Complete)
+}
+
+/// the address and the length, if given, of a location like `0x1000:16` or `malloc+8`
+fn locate(
+    process: &Process,
+    pages: &[VirtMemoryPage],
+    location: &str,
+) -> Result<(u64, Option<u64>)> {
+    let (location, length) = match location.rsplit_once(':') {
+        Some((location, length)) => (location, Some(parse_number(length)?)),
+        None => (location, None),
+    };
+    if let Ok(address) = parse_address(location) {
+        return Ok((address, length));
+    }
+    // the name may contain a `+` itself, e.g. libstdc++.so.6
+    let (name, offset) = match location.rsplit_once('+') {
+        Some((name, offset)) if !name.is_empty() && parse_number(offset).is_ok() => {
+            (name, parse_number(offset)?)
+        }
+        _ => (location, 0),
+    };
+    let base = match mapping_address(pages, name) {
+        Some(address) => address,
+        None => process
+            .symbol_address(name)?
+            .ok_or_else(|| Error::InvalidArgument(format!("unknown symbol or mapping '{name}'")))?,
+    };
+    let address = base
+        .checked_add(offset)
+        .ok_or_else(|| Error::InvalidArgument(format!("{location} is out of range")))?;
+    Ok((address, length))
+}
+
+/// the lowest address of the mappings whose pathname or file name is `name`
+fn mapping_address(pages: &[VirtMemoryPage], name: &str) -> Option<u64> {
+    pages
+        .iter()
+        .filter(|page| {
+            page.file_path == name
+                || !page.file_path.starts_with('[')
+                    && Path::new(&page.file_path).file_name() == Some(name.as_ref())
+        })
+        .map(|page| page.from)
+        .min()
+}
+
+/// parse a decimal number or a hex number with `0x` prefix
+fn parse_number(s: &str) -> Result<u64> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+    .ok_or_else(|| Error::InvalidArgument(format!("invalid number '{s}'")))
+}
+
+/// number of bytes of `address..address + length` in consecutive readable pages starting at `address`
+fn readable_length(pages: &[VirtMemoryPage], address: u64, length: u64) -> u64 {
+    let end = address.saturating_add(length);
+    let mut readable_to = address;
+    while readable_to < end {
+        match pages
+            .iter()
+            .find(|page| page.from <= readable_to && readable_to < page.to && page.is_readable())
+        {
+            Some(page) => readable_to = page.to,
+            None => break,
+        }
+    }
+    readable_to.min(end) - address
+}
+
+/// print `buf` like `hexdump -C` with the addresses of the process
+fn hexdump(out: &mut impl Write, address: u64, buf: &[u8]) -> io::Result<()> {
+    for (i, line) in buf.chunks(HEXDUMP_WIDTH).enumerate() {
+        write!(out, "{:016x} ", address + (i * HEXDUMP_WIDTH) as u64)?;
+        for column in 0..HEXDUMP_WIDTH {
+            if column % 8 == 0 {
+                write!(out, " ")?;
+            }
+            match line.get(column) {
+                Some(byte) => write!(out, "{byte:02x} ")?,
+                None => write!(out, "   ")?,
+            }
+        }
+        let ascii = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        writeln!(out, " |{ascii}|")?;
+    }
+    Ok(())
+}
+
+/// print the values in `buf` one per line with their address, the length of `buf` is a multiple of their size
+fn print_values(
+    out: &mut impl Write,
+    pages: &[VirtMemoryPage],
+    address: u64,
+    buf: &[u8],
+    value_type: ReadType,
+    endian: Endian,
+) -> io::Result<()> {
+    let Some(size) = value_type.size() else {
+        // C string
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let string = String::from_utf8_lossy(&buf[..end]);
+        return writeln!(out, "{address:#x}: {string:?}");
+    };
+    for (i, value) in buf.chunks_exact(size as usize).enumerate() {
+        let mut value = value.to_vec();
+        if endian != Endian::native() {
+            value.reverse();
+        }
+        let value_address = address + i as u64 * size;
+        match value_type {
+            ReadType::Value(value_type) => {
+                writeln!(out, "{value_address:#x}: {}", value_type.format(&value))?
+            }
+            ReadType::Pointer => {
+                let pointer = usize::from_ne_bytes(value.try_into().unwrap()) as u64;
+                write!(out, "{value_address:#x}: {pointer:#x}")?;
+                // where the pointer points to if it is mapped
+                match pages.iter().find(|p| p.from <= pointer && pointer < p.to) {
+                    Some(page) => writeln!(
+                        out,
+                        " {}+{:#x}",
+                        display_path(&page.file_path),
+                        pointer - page.from
+                    )?,
+                    None => writeln!(out)?,
+                }
+            }
+            ReadType::CString => unreachable!("C strings have no size"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use process_memory::{restore, Filter, FreezeMethod, Manifest, Process, Result};
+
+use super::{display_path, TargetArgs};
+use crate::Outcome;
+
+/// Write a dump back into the running process
+///
+/// Writable regions whose mapping still has the same address range and pathname are restored,
+/// only the bytes that changed since the dump are written. Other regions are skipped.
+#[derive(Args)]
+#[command(mut_group("TargetArgs", |group| group.required(false)))]
+pub struct RestoreArgs {
+    /// dump directory, core file or tar archive
+    dump: PathBuf,
+    /// the process to restore, the PID in the manifest of the dump by default
+    #[command(flatten)]
+    target: Option<TargetArgs>,
+    /// only restore regions matching FILTER, see the help of dump
+    #[arg(short, long = "filter", value_name = "FILTER")]
+    filters: Vec<Filter>,
+    /// stop the process while it is restored, METHOD is signal or ptrace
+    #[arg(
+        long,
+        value_name = "METHOD",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "signal"
+    )]
+    freeze: Option<FreezeMethod>,
+    /// only print what would be written
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub fn run(args: RestoreArgs) -> Result<Outcome> {
+    let process = match &args.target {
+        Some(target) => target.resolve()?,
+        None => Process::new(Manifest::of_dump(&args.dump)?.process.pid)?,
+    };
+    let filter = if args.filters.is_empty() {
+        Filter::All
+    } else {
+        Filter::any(args.filters)
+    };
+    let frozen = args
+        .freeze
+        .map(|method| process.freeze(method))
+        .transpose()?;
+    let restored = restore(&process, &args.dump, &filter, args.dry_run)?;
+    if let Some(frozen) = frozen {
+        frozen.resume()?;
+    }
+
+    let mut outcome = Outcome::Complete;
+    let (mut written, mut skipped) = (0, 0);
+    for region in restored {
+        let page = &region.page;
+        let name = format!(
+            "{:#x}-{:#x} {} {}",
+            page.from,
+            page.to,
+            page.perms(),
+            display_path(&page.file_path)
+        );
+        if let Some(reason) = region.skipped {
+            eprintln!("skip {name}: {reason}");
+            skipped += 1;
+            continue;
+        }
+        if let Some(err) = &region.error {
+            eprintln!("partial {name}: {err}");
+            outcome = Outcome::Partial;
+        } else if region.unreadable {
+            eprintln!("partial {name}: could not be compared completely");
+            outcome = Outcome::Partial;
+        }
+        if !region.changes.is_empty() {
+            println!(
+                "{} {name}: {} bytes in {} ranges",
+                if args.dry_run { "would write" } else { "write" },
+                region.changed_bytes(),
+                region.changes.len()
+            );
+            written += region.changed_bytes();
+        }
+    }
+    eprintln!(
+        "{} {written} bytes, skipped {skipped} regions",
+        if args.dry_run { "would write" } else { "wrote" }
+    );
+    Ok(outcome)
+}
@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Args, Subcommand};
+use process_memory::{Backend, Condition, Filter, FreezeMethod, Process, Result, Scan, ValueType};
+
+use super::{SelectArgs, TargetArgs};
+use crate::Outcome;
+
+/// Find the address of a value by scanning for it and narrowing the candidates down
+///
+/// `scan new` finds all addresses holding a value, every `scan next` keeps only the candidates
+/// whose value changed in the given way since the previous scan.
+/// The candidates are stored in a state file between the runs.
+#[derive(Args)]
+pub struct ScanArgs {
+    #[command(subcommand)]
+    command: ScanCommand,
+}
+
+#[derive(Subcommand)]
+enum ScanCommand {
+    New(NewArgs),
+    Next(NextArgs),
+    Show(ShowArgs),
+}
+
+/// where the scan is stored and how much of it is printed
+#[derive(Args)]
+struct StateArgs {
+    /// file the candidates are stored in
+    #[arg(short, long, default_value = "scan.json")]
+    state: PathBuf,
+    /// print at most LIMIT candidates, 0 prints all
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+}
+
+/// Start a new scan for all addresses holding VALUE
+///
+/// By default all readable and writable pages are scanned.
+#[derive(Args)]
+struct NewArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+    #[command(flatten)]
+    select: SelectArgs,
+    #[command(flatten)]
+    state: StateArgs,
+    /// type of the value: u8, u16, u32, u64, i8, i16, i32, i64, f32, f64 or string
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    value_type: ValueType,
+    /// also find numbers that are not aligned to their size
+    #[arg(long)]
+    unaligned: bool,
+    value: String,
+}
+
+/// Keep only the candidates matching a condition
+#[derive(Args)]
+#[command(group(ArgGroup::new("condition").required(true)))]
+struct NextArgs {
+    #[command(flatten)]
+    state: StateArgs,
+    /// the value is different from the previous scan
+    #[arg(long, group = "condition")]
+    changed: bool,
+    /// the value is the same as in the previous scan
+    #[arg(long, group = "condition")]
+    unchanged: bool,
+    /// the value is greater than in the previous scan
+    #[arg(long, group = "condition")]
+    increased: bool,
+    /// the value is less than in the previous scan
+    #[arg(long, group = "condition")]
+    decreased: bool,
+    /// the value is VALUE
+    #[arg(long, group = "condition", value_name = "VALUE")]
+    equals: Option<String>,
+    /// how to read the memory: vm-readv, proc-mem or auto
+    #[arg(long, default_value = "auto")]
+    backend: Backend,
+    /// stop the process while it is read, METHOD is signal or ptrace
+    #[arg(
+        long,
+        value_name = "METHOD",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "signal"
+    )]
+    freeze: Option<FreezeMethod>,
+}
+
+impl NextArgs {
+    fn condition(&self, value_type: ValueType) -> Result<Condition> {
+        Ok(if let Some(value) = &self.equals {
+            Condition::Equals(value_type.encode(value)?)
+        } else if self.changed {
+            Condition::Changed
+        } else if self.unchanged {
+            Condition::Unchanged
+        } else if self.increased {
+            Condition::Increased
+        } else {
+            Condition::Decreased
+        })
+    }
+}
+
+/// Print the candidates of the current scan
+#[derive(Args)]
+struct ShowArgs {
+    #[command(flatten)]
+    state: StateArgs,
+}
+
+pub fn run(args: ScanArgs) -> Result<Outcome> {
+    match args.command {
+        ScanCommand::New(args) => new(args),
+        ScanCommand::Next(args) => next(args),
+        ScanCommand::Show(args) => {
+            print(&Scan::read(&args.state.state)?, args.state.limit);
+            Ok(Outcome::Complete)
+        }
+    }
+}
+
+fn new(args: NewArgs) -> Result<Outcome> {
+    let value = args.value_type.encode(&args.value)?;
+    let process = args.target.resolve()?;
+    let frozen = args
+        .select
+        .freeze
+        .map(|method| process.freeze(method))
+        .transpose()?;
+
+    let pages = args
+        .select
+        .pages(&process, Filter::readable_writable())?
+        .into_iter()
+        .filter(|m| m.is_readable())
+        .collect::<Vec<_>>();
+    let scan = Scan::first(
+        &process,
+        &mut process.memory_with(args.select.backend)?,
+        &pages,
+        args.value_type,
+        &value,
+        args.unaligned,
+    )?;
+    if let Some(frozen) = frozen {
+        frozen.resume()?;
+    }
+
+    scan.write(&args.state.state)?;
+    print(&scan, args.state.limit);
+    Ok(Outcome::Complete)
+}
+
+fn next(args: NextArgs) -> Result<Outcome> {
+    let mut scan = Scan::read(&args.state.state)?;
+    let condition = args.condition(scan.value_type)?;
+    let process = Process::new(scan.pid)?;
+    scan.check_process(&process)?;
+    let frozen = args
+        .freeze
+        .map(|method| process.freeze(method))
+        .transpose()?;
+
+    scan.narrow(&mut process.memory_with(args.backend)?, &condition)?;
+    if let Some(frozen) = frozen {
+        frozen.resume()?;
+    }
+
+    scan.write(&args.state.state)?;
+    print(&scan, args.state.limit);
+    Ok(Outcome::Complete)
+}
+
+/// print the number of candidates and the first `limit` of them
+fn print(scan: &Scan, limit: usize) {
+    eprintln!("{} candidates", scan.candidates.len());
+    let limit = if limit == 0 { usize::MAX } else { limit };
+    for candidate in scan.candidates.iter().take(limit) {
+        println!(
+            "{:#x} {}",
+            candidate.address,
+            scan.value_type.format(&candidate.value)
+        );
+    }
+}
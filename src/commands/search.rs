@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Args};
+use process_memory::{search, Filter, Pattern, Result, Snapshot};
+
+use super::{display_path, SelectArgs, TargetArgs};
+use crate::Outcome;
+
+/// Search the memory of a process for a pattern
+///
+/// By default all readable pages are searched, a dump can be searched with --dump. Every match is printed as its address,
+/// the pathname of the page and the offset inside of the page.
+#[derive(Args)]
+#[command(group(ArgGroup::new("pattern").required(true)))]
+pub struct SearchArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+    /// search a dump directory, core file or tar archive instead of a running process
+    #[arg(
+        long,
+        group = "TargetArgs",
+        conflicts_with_all = ["pid", "name", "cmdline", "exe", "ppid", "freeze"]
+    )]
+    dump: Option<PathBuf>,
+    #[command(flatten)]
+    select: SelectArgs,
+    /// hex bytes separated by spaces, ?? matches any byte, e.g. '48 8B ?? ?? 89'
+    #[arg(long, group = "pattern")]
+    hex: Option<String>,
+    /// UTF-8 encoded string
+    #[arg(long, group = "pattern")]
+    string: Option<String>,
+    /// UTF-16 little endian encoded string
+    #[arg(long, group = "pattern")]
+    utf16: Option<String>,
+    /// regular expression matched against the raw bytes
+    #[arg(long, group = "pattern")]
+    regex: Option<String>,
+}
+
+impl SearchArgs {
+    fn pattern(&self) -> Result<Pattern> {
+        if let Some(hex) = &self.hex {
+            Pattern::hex(hex)
+        } else if let Some(string) = &self.string {
+            Ok(Pattern::string(string))
+        } else if let Some(string) = &self.utf16 {
+            Ok(Pattern::utf16(string))
+        } else {
+            Pattern::regex(self.regex.as_deref().unwrap_or_default())
+        }
+    }
+}
+
+pub fn run(args: SearchArgs) -> Result<Outcome> {
+    let pattern = args.pattern()?;
+    let filter = args.select.filter(Filter::All);
+    let (mut memory, frozen) = match &args.dump {
+        Some(path) => (Snapshot::open(path)?, None),
+        None => {
+            let process = args.target.resolve()?;
+            let frozen = args
+                .select
+                .freeze
+                .map(|method| process.freeze(method))
+                .transpose()?;
+            let mut snapshot = Snapshot::live(&process, args.select.backend)?;
+            if filter.needs_smaps() {
+                snapshot.pages = process.smaps()?;
+            }
+            (snapshot, frozen)
+        }
+    };
+
+    let pages = memory
+        .pages
+        .iter()
+        .filter(|m| m.is_readable() && filter.matches(m))
+        .cloned()
+        .collect::<Vec<_>>();
+    let matches = search(&mut memory, &pages, &pattern)?;
+    if let Some(frozen) = frozen {
+        frozen.resume()?;
+    }
+
+    for m in matches {
+        println!(
+            "{:#x} {}+{:#x}",
+            m.address,
+            display_path(&m.page.file_path),
+            m.offset
+        );
+    }
+    Ok(Outcome::Complete)
+}
@@ -0,0 +1,91 @@
+use clap::{ArgGroup, Args};
+use process_memory::{Error, Result, ValueType};
+
+use super::{parse_address, TargetArgs};
+use crate::Outcome;
+
+/// Write bytes or a value into the memory of a process
+///
+/// The previous content is printed so that the change can be undone.
+#[derive(Args)]
+#[command(group(ArgGroup::new("data").required(true)))]
+pub struct WriteArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+    /// hex address to write to
+    #[arg(value_parser = parse_address)]
+    address: u64,
+    /// hex bytes to write, e.g. '90 90 90'
+    #[arg(long, group = "data")]
+    hex: Option<String>,
+    /// type of VALUE: u8, u16, u32, u64, i8, i16, i32, i64, f32, f64 or string
+    #[arg(
+        short = 't',
+        long = "type",
+        value_name = "TYPE",
+        group = "data",
+        requires = "value"
+    )]
+    value_type: Option<ValueType>,
+    /// the value to write, in native byte order
+    #[arg(requires = "value_type")]
+    value: Option<String>,
+    /// also write to pages that are not writable, e.g. to patch code
+    #[arg(long)]
+    force: bool,
+}
+
+impl WriteArgs {
+    fn bytes(&self) -> Result<Vec<u8>> {
+        match (&self.hex, self.value_type) {
+            (Some(hex), _) => parse_hex(hex),
+            (None, Some(value_type)) => {
+                value_type.encode(self.value.as_deref().unwrap_or_default())
+            }
+            (None, None) => unreachable!("clap requires --hex or --type"),
+        }
+    }
+}
+
+pub fn run(args: WriteArgs) -> Result<Outcome> {
+    let bytes = args.bytes()?;
+    let process = args.target.resolve()?;
+    let old = process.write_memory(args.address, &bytes, args.force)?;
+
+    println!(
+        "wrote {} bytes at {:#x}: {} -> {}",
+        bytes.len(),
+        args.address,
+        format_hex(&old),
+        format_hex(&bytes)
+    );
+    eprintln!(
+        "undo with: process-memory write --pid {} {:#x} --hex '{}'{}",
+        process.pid(),
+        args.address,
+        format_hex(&old),
+        if args.force { " --force" } else { "" }
+    );
+    Ok(Outcome::Complete)
+}
+
+/// parse hex bytes that may be separated by whitespace
+fn parse_hex(hex: &str) -> Result<Vec<u8>> {
+    let invalid = || Error::InvalidArgument(format!("invalid hex bytes '{hex}'"));
+    let digits = hex.split_whitespace().collect::<String>();
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid()))
+        .collect()
+}
+
+fn format_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use flate2::{read::MultiGzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Sink};
+
+/// zstd level used for dumps, a fast level because dumps are large
+const ZSTD_LEVEL: i32 = 3;
+
+/// how the output files of a dump are compressed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+    Gzip,
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "zstd" => Ok(Self::Zstd),
+            "gzip" => Ok(Self::Gzip),
+            _ => Err(Error::InvalidArgument(format!("unknown compression '{s}'"))),
+        }
+    }
+}
+
+impl Compression {
+    /// the extension appended to the names of compressed files including the dot, empty for `None`
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Zstd => ".zst",
+            Self::Gzip => ".gz",
+        }
+    }
+
+    /// the compression of the file at `path` according to its first bytes
+    pub fn detect(path: &Path) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        let read = File::open(path)?.read(&mut magic)?;
+        Ok(match &magic[..read] {
+            [0x28, 0xb5, 0x2f, 0xfd] => Self::Zstd,
+            [0x1f, 0x8b, ..] => Self::Gzip,
+            _ => Self::None,
+        })
+    }
+
+    /// compress everything written to the returned encoder into `to`
+    pub fn encoder<W: Write>(self, to: W) -> io::Result<Encoder<W>> {
+        Ok(match self {
+            Self::None => Encoder::None(to),
+            Self::Zstd => Encoder::Zstd(zstd::Encoder::new(to, ZSTD_LEVEL)?),
+            Self::Gzip => Encoder::Gzip(GzEncoder::new(to, flate2::Compression::default())),
+        })
+    }
+
+    /// decompress `from` while it is read
+    pub fn decoder<'a, R: Read + 'a>(self, from: R) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::None => Box::new(from),
+            Self::Zstd => Box::new(zstd::Decoder::new(from)?),
+            Self::Gzip => Box::new(MultiGzDecoder::new(BufReader::new(from))),
+        })
+    }
+}
+
+/// a writer compressing the data while it is written, `finish` has to be called at the end
+pub enum Encoder<W: Write> {
+    None(W),
+    Zstd(zstd::Encoder<'static, W>),
+    Gzip(GzEncoder<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    /// write the end of the compressed stream and return the underlying writer
+    pub fn finish(self) -> io::Result<W> {
+        match self {
+            Self::None(to) => Ok(to),
+            Self::Zstd(encoder) => encoder.finish(),
+            Self::Gzip(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::None(to) => to.write(buf),
+            Self::Zstd(encoder) => encoder.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::None(to) => to.flush(),
+            Self::Zstd(encoder) => encoder.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// zeros are only skipped if the data isn't compressed, otherwise they are compressed like any other data
+impl<W: Sink> Sink for Encoder<W> {
+    fn write_zeros(&mut self, n: u64) -> io::Result<()> {
+        match self {
+            Self::None(to) => to.write_zeros(n),
+            _ => {
+                io::copy(&mut io::repeat(0).take(n), self)?;
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::ops::Range;
+
+use crate::{page_size, Error, PagemapEntry, ProcessMemory, Result, Sink, VirtMemoryPage};
+
+/// size of the buffer used to copy pages
+const COPY_CHUNK_SIZE: usize = 1 << 20;
+
+/// outcome of copying a page of memory
+#[derive(Debug, Default)]
+pub struct Copied {
+    pub read: u64,              // number of bytes that could be read
+    pub unpopulated: u64,       // number of bytes skipped because they were never touched
+    pub holes: Vec<Range<u64>>, // address ranges that could not be read and were zero filled
+    pub error: Option<Error>,   // the first error that occurred while reading
+}
+
+impl Copied {
+    fn add_hole(&mut self, hole: Range<u64>, error: Error) {
+        match self.holes.last_mut() {
+            Some(last) if last.end == hole.start => last.end = hole.end,
+            _ => self.holes.push(hole),
+        }
+        self.error.get_or_insert(error);
+    }
+}
+
+impl ProcessMemory {
+    /// copy the whole content of `page` to `to`, exactly `page.size()` bytes are written
+    ///
+    /// The page is copied in large chunks. If a chunk can't be read completely it is read again
+    /// page by page, pages that can't be read are zero filled and recorded as holes.
+    /// Pages of private anonymous mappings that are not populated according to `/proc/PID/pagemap`
+    /// are not read at all but written as zeros.
+    /// Only errors that make reading impossible at all or writing errors are returned.
+    pub fn copy_page<S: Sink>(&mut self, page: &VirtMemoryPage, mut to: S) -> Result<Copied> {
+        let page_size = page_size();
+        let mut buf = vec![0; COPY_CHUNK_SIZE.min(page.size() as usize)];
+        let mut copied = Copied::default();
+        let mut address = page.from;
+        while address < page.to {
+            let mut len = buf.len().min((page.to - address) as usize);
+            if let Some(entries) = self.pagemap_entries(page, address, len) {
+                let unpopulated = entries.iter().take_while(|e| !e.populated()).count();
+                if unpopulated > 0 {
+                    let skip = (unpopulated as u64 * page_size).min(page.to - address);
+                    to.write_zeros(skip).map_err(Error::Output)?;
+                    copied.unpopulated += skip;
+                    address += skip;
+                    continue;
+                }
+                // only read populated pages at once
+                let populated = entries.iter().take_while(|e| e.populated()).count();
+                len = len.min(populated * page_size as usize);
+            }
+
+            let read = match self.read_partial(address, &mut buf[..len]) {
+                Ok(read) => read,
+                Err(err) if err.is_recoverable() => 0,
+                Err(err) => return Err(err),
+            };
+            to.write_all(&buf[..read]).map_err(Error::Output)?;
+            copied.read += read as u64;
+            address += read as u64;
+            if read == len {
+                continue;
+            }
+
+            // read the rest of the chunk page by page
+            let end = address + (len - read) as u64;
+            while address < end {
+                let next = (address + 1).next_multiple_of(page_size).min(end);
+                let page_buf = &mut buf[..(next - address) as usize];
+                let (read, error) = match self.read_partial(address, page_buf) {
+                    Ok(read) => (read, None),
+                    Err(err) if err.is_recoverable() => (0, Some(err)),
+                    Err(err) => return Err(err),
+                };
+                to.write_all(&page_buf[..read]).map_err(Error::Output)?;
+                to.write_zeros((page_buf.len() - read) as u64)
+                    .map_err(Error::Output)?;
+                copied.read += read as u64;
+                if read < page_buf.len() {
+                    let error = error.unwrap_or(Error::ShortRead {
+                        address,
+                        expected: page_buf.len(),
+                        read,
+                    });
+                    copied.add_hole(address + read as u64..next, error);
+                }
+                address = next;
+            }
+        }
+        Ok(copied)
+    }
+
+    /// the pagemap entries of `len` bytes at `address` inside of `page`
+    /// if unpopulated pages of it are known to only contain zeros
+    fn pagemap_entries(
+        &self,
+        page: &VirtMemoryPage,
+        address: u64,
+        len: usize,
+    ) -> Option<Vec<PagemapEntry>> {
+        // unpopulated pages of file mappings contain the file content, those of shared memory may be in use
+        if page.inode != 0 || page.shared {
+            return None;
+        }
+        self.pagemap
+            .as_ref()?
+            .entries(address, address + len as u64)
+            .ok()
+    }
+}
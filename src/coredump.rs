@@ -0,0 +1,273 @@
+use crate::{
+    page_size, DumpedPage, Error, Process, ProcessMemory, Result, Sink, Thread, VirtMemoryPage,
+};
+
+const EHDR_SIZE: u64 = 64;
+const PHDR_SIZE: u64 = 56;
+const SHDR_SIZE: u64 = 64;
+
+const ET_CORE: u16 = 4;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PN_XNUM: u16 = 0xffff; // e_phnum if the real number is stored in the first section header
+
+pub(crate) const NT_PRSTATUS: u32 = 1;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_FILE: u32 = 0x46494c45;
+
+#[cfg(target_arch = "x86_64")]
+const EM_MACHINE: u16 = 62; // EM_X86_64
+#[cfg(target_arch = "x86_64")]
+pub(crate) const ELF_NGREG: usize = 27;
+#[cfg(target_arch = "aarch64")]
+const EM_MACHINE: u16 = 183; // EM_AARCH64
+#[cfg(target_arch = "aarch64")]
+pub(crate) const ELF_NGREG: usize = 34;
+
+#[cfg(target_endian = "little")]
+const ELFDATA: u8 = 1; // ELFDATA2LSB
+#[cfg(target_endian = "big")]
+const ELFDATA: u8 = 2; // ELFDATA2MSB
+
+/// write an ELF core file of `process` containing `pages` to `out`
+///
+/// Each page becomes a `PT_LOAD` segment, the `PT_NOTE` segment contains a `NT_PRSTATUS` note per thread
+/// of `threads`, `NT_PRPSINFO`, `NT_AUXV` and `NT_FILE`. Parts of pages that could not be read are zero filled.
+/// The registers of threads whose registers could not be read are zero.
+pub fn write_core<S: Sink>(
+    process: &Process,
+    threads: &[Thread],
+    memory: &mut ProcessMemory,
+    pages: Vec<VirtMemoryPage>,
+    mut out: S,
+) -> Result<Vec<DumpedPage>> {
+    let page_size = page_size();
+    let notes = notes(process, threads, &pages, page_size)?;
+
+    let phnum = pages.len() as u64 + 1;
+    let shnum = if phnum >= PN_XNUM as u64 { 1 } else { 0 };
+    let notes_offset = EHDR_SIZE + phnum * PHDR_SIZE + shnum * SHDR_SIZE;
+    let data_offset = (notes_offset + notes.len() as u64).next_multiple_of(page_size);
+
+    let mut headers = Vec::new();
+    // ELF header
+    headers.extend_from_slice(&[0x7f, b'E', b'L', b'F', 2, ELFDATA, 1, 0]); // ELFCLASS64, EV_CURRENT, ELFOSABI_NONE
+    headers.extend_from_slice(&[0; 8]);
+    push16(&mut headers, ET_CORE);
+    push16(&mut headers, EM_MACHINE);
+    push32(&mut headers, 1); // e_version
+    push64(&mut headers, 0); // e_entry
+    push64(&mut headers, EHDR_SIZE); // e_phoff
+    push64(
+        &mut headers,
+        if shnum > 0 {
+            EHDR_SIZE + phnum * PHDR_SIZE
+        } else {
+            0
+        },
+    ); // e_shoff
+    push32(&mut headers, 0); // e_flags
+    push16(&mut headers, EHDR_SIZE as u16);
+    push16(&mut headers, PHDR_SIZE as u16);
+    push16(&mut headers, if shnum > 0 { PN_XNUM } else { phnum as u16 });
+    push16(&mut headers, SHDR_SIZE as u16);
+    push16(&mut headers, shnum as u16);
+    push16(&mut headers, 0); // e_shstrndx
+
+    // program headers
+    push_phdr(
+        &mut headers,
+        PT_NOTE,
+        0,
+        notes_offset,
+        0,
+        notes.len() as u64,
+        0,
+        4,
+    );
+    let mut offset = data_offset;
+    for page in &pages {
+        // like the kernel, don't include the contents of pages that are not readable at all
+        let file_size = if page.is_readable() { page.size() } else { 0 };
+        push_phdr(
+            &mut headers,
+            PT_LOAD,
+            page.mode as u32, // PF_X, PF_W and PF_R have the same values as the modes
+            offset,
+            page.from,
+            file_size,
+            page.size(),
+            page_size,
+        );
+        offset += file_size;
+    }
+
+    // section header holding the real number of program headers
+    if shnum > 0 {
+        headers.extend_from_slice(&[0; 44]); // sh_name ... sh_link
+        push32(&mut headers, phnum as u32); // sh_info
+        headers.extend_from_slice(&[0; 16]); // sh_addralign, sh_entsize
+    }
+
+    out.write_all(&headers).map_err(Error::Output)?;
+    out.write_all(&notes).map_err(Error::Output)?;
+    out.write_zeros(data_offset - notes_offset - notes.len() as u64)
+        .map_err(Error::Output)?;
+
+    let mut dumped = Vec::new();
+    for page in pages {
+        if !page.is_readable() {
+            dumped.push(DumpedPage {
+                page,
+                file: None,
+                read: 0,
+                unpopulated: 0,
+                holes: Vec::new(),
+                error: None,
+            });
+            continue;
+        }
+        dumped.push(DumpedPage::copy(memory, page, &mut out)?);
+    }
+    out.flush().map_err(Error::Output)?;
+    Ok(dumped)
+}
+
+/// build the content of the `PT_NOTE` segment
+fn notes(
+    process: &Process,
+    threads: &[Thread],
+    pages: &[VirtMemoryPage],
+    page_size: u64,
+) -> Result<Vec<u8>> {
+    let stat = process.stat()?;
+    let (uid, gid) = process.uid_gid()?;
+    let mut notes = Vec::new();
+
+    // NT_PRSTATUS, the main thread has to be the first one
+    for thread in threads {
+        let mut prstatus = Vec::new();
+        prstatus.extend_from_slice(&[0; 16]); // pr_info, pr_cursig
+        push64(&mut prstatus, 0); // pr_sigpend
+        push64(&mut prstatus, 0); // pr_sighold
+        push32(&mut prstatus, thread.tid);
+        push32(&mut prstatus, stat.ppid);
+        push32(&mut prstatus, stat.pgrp as u32);
+        push32(&mut prstatus, stat.session as u32);
+        let times = [
+            thread.stat.utime,
+            thread.stat.stime,
+            thread.stat.cutime,
+            thread.stat.cstime,
+        ];
+        for ticks in times {
+            push_timeval(&mut prstatus, ticks);
+        }
+        // pr_reg
+        match &thread.registers {
+            Some(registers) => registers.0.iter().for_each(|&r| push64(&mut prstatus, r)),
+            None => prstatus.extend_from_slice(&[0; ELF_NGREG * 8]),
+        }
+        push32(&mut prstatus, 0); // pr_fpvalid
+        prstatus.extend_from_slice(&[0; 4]);
+        push_note(&mut notes, NT_PRSTATUS, &prstatus);
+    }
+
+    // NT_PRPSINFO
+    let mut prpsinfo = Vec::new();
+    let state = "RSDTZW".find(stat.state).unwrap_or(0);
+    prpsinfo.push(state as u8); // pr_state
+    prpsinfo.push(stat.state as u8); // pr_sname
+    prpsinfo.push((stat.state == 'Z') as u8); // pr_zomb
+    prpsinfo.push(stat.nice as i8 as u8); // pr_nice
+    prpsinfo.extend_from_slice(&[0; 4]);
+    push64(&mut prpsinfo, stat.flags as u64);
+    push32(&mut prpsinfo, uid);
+    push32(&mut prpsinfo, gid);
+    push32(&mut prpsinfo, process.pid());
+    push32(&mut prpsinfo, stat.ppid);
+    push32(&mut prpsinfo, stat.pgrp as u32);
+    push32(&mut prpsinfo, stat.session as u32);
+    push_str(&mut prpsinfo, &process.comm()?, 16); // pr_fname
+    push_str(&mut prpsinfo, &process.cmdline()?.join(" "), 80); // pr_psargs
+    push_note(&mut notes, NT_PRPSINFO, &prpsinfo);
+
+    push_note(&mut notes, NT_AUXV, &process.auxv()?);
+
+    // NT_FILE: count, page size, (start, end, offset in pages) per file and the file names
+    let files = pages.iter().filter(|p| p.inode != 0).collect::<Vec<_>>();
+    let mut file = Vec::new();
+    push64(&mut file, files.len() as u64);
+    push64(&mut file, page_size);
+    for page in &files {
+        push64(&mut file, page.from);
+        push64(&mut file, page.to);
+        push64(&mut file, page.offset / page_size);
+    }
+    for page in &files {
+        file.extend_from_slice(page.file_path.as_bytes());
+        file.push(0);
+    }
+    push_note(&mut notes, NT_FILE, &file);
+
+    Ok(notes)
+}
+
+fn push16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_ne_bytes());
+}
+
+fn push32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_ne_bytes());
+}
+
+fn push64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_ne_bytes());
+}
+
+/// push `s` as a NUL terminated string of exactly `len` bytes
+fn push_str(buf: &mut Vec<u8>, s: &str, len: usize) {
+    let bytes = &s.as_bytes()[..s.len().min(len - 1)];
+    buf.extend_from_slice(bytes);
+    buf.resize(buf.len() + len - bytes.len(), 0);
+}
+
+/// push a `struct timeval` for a time given in clock ticks
+fn push_timeval(buf: &mut Vec<u8>, ticks: u64) {
+    // SAFETY: sysconf has no preconditions
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+    push64(buf, ticks / ticks_per_second);
+    push64(buf, ticks % ticks_per_second * 1_000_000 / ticks_per_second);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_phdr(
+    buf: &mut Vec<u8>,
+    kind: u32,
+    flags: u32,
+    offset: u64,
+    address: u64,
+    file_size: u64,
+    memory_size: u64,
+    align: u64,
+) {
+    push32(buf, kind);
+    push32(buf, flags);
+    push64(buf, offset);
+    push64(buf, address); // p_vaddr
+    push64(buf, 0); // p_paddr
+    push64(buf, file_size);
+    push64(buf, memory_size);
+    push64(buf, align);
+}
+
+/// push a note with the name `CORE`, name and description are padded to 4 bytes
+fn push_note(buf: &mut Vec<u8>, kind: u32, desc: &[u8]) {
+    push32(buf, 5); // namesz including the NUL
+    push32(buf, desc.len() as u32);
+    push32(buf, kind);
+    buf.extend_from_slice(b"CORE\0\0\0\0");
+    buf.extend_from_slice(desc);
+    buf.resize(buf.len().next_multiple_of(4), 0);
+}
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{manifest::hex, page_size, Filter, Memory, Result, Snapshot, VirtMemoryPage};
+
+/// number of bytes compared at once
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// differences between two snapshots of the memory of a process
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Diff {
+    pub added: Vec<Mapping>,   // only in the new snapshot
+    pub removed: Vec<Mapping>, // only in the old snapshot
+    pub changed: Vec<ChangedRegion>,
+}
+
+/// a mapping, addresses are serialized as hex strings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mapping {
+    #[serde(with = "hex")]
+    pub start: u64,
+    #[serde(with = "hex")]
+    pub end: u64,
+    pub perms: String,
+    pub path: String,
+}
+
+/// a mapping that exists in both snapshots but whose permissions or content changed
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedRegion {
+    #[serde(with = "hex")]
+    pub start: u64,
+    #[serde(with = "hex")]
+    pub end: u64,
+    pub path: String,
+    pub old_perms: String,
+    pub new_perms: String,
+    pub changes: Vec<ChangedRange>,
+    pub unreadable: bool, // the content could not be compared completely
+}
+
+/// absolute address range of bytes that differ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedRange {
+    #[serde(with = "hex")]
+    pub start: u64,
+    #[serde(with = "hex")]
+    pub end: u64,
+}
+
+impl From<&VirtMemoryPage> for Mapping {
+    fn from(page: &VirtMemoryPage) -> Self {
+        Self {
+            start: page.from,
+            end: page.to,
+            perms: page.perms(),
+            path: page.file_path.clone(),
+        }
+    }
+}
+
+impl ChangedRegion {
+    /// number of changed bytes
+    pub fn changed_bytes(&self) -> u64 {
+        self.changes.iter().map(|c| c.end - c.start).sum()
+    }
+}
+
+/// compare the pages of `old` and `new` matching `filter`
+///
+/// Pages are matched by their address range, pages whose range changed are reported as removed and added.
+/// Pages that existed in the other snapshot but weren't dumped there are neither compared nor reported.
+pub fn diff(old: &mut Snapshot, new: &mut Snapshot, filter: &Filter) -> Result<Diff> {
+    let old_mappings = ranges(&old.mappings);
+    let new_mappings = ranges(&new.mappings);
+    let old_pages = old
+        .pages
+        .iter()
+        .filter(|p| filter.matches(p))
+        .map(|p| ((p.from, p.to), p.clone()))
+        .collect::<HashMap<_, _>>();
+    let new_pages = new
+        .pages
+        .iter()
+        .filter(|p| filter.matches(p))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut diff = Diff::default();
+    for new_page in &new_pages {
+        let Some(old_page) = old_pages.get(&(new_page.from, new_page.to)) else {
+            if !old_mappings.contains(&(new_page.from, new_page.to)) {
+                diff.added.push(new_page.into());
+            }
+            continue;
+        };
+        let (changes, unreadable) = compare(old, new, new_page)?;
+        if !changes.is_empty() || unreadable || old_page.perms() != new_page.perms() {
+            diff.changed.push(ChangedRegion {
+                start: new_page.from,
+                end: new_page.to,
+                path: new_page.file_path.clone(),
+                old_perms: old_page.perms(),
+                new_perms: new_page.perms(),
+                changes,
+                unreadable,
+            });
+        }
+    }
+    for old_page in old_pages.values() {
+        if !new_mappings.contains(&(old_page.from, old_page.to)) {
+            diff.removed.push(old_page.into());
+        }
+    }
+    diff.removed.sort_by_key(|m| m.start);
+    Ok(diff)
+}
+
+/// the address ranges of `mappings`
+fn ranges(mappings: &[Mapping]) -> HashSet<(u64, u64)> {
+    mappings.iter().map(|m| (m.start, m.end)).collect()
+}
+
+/// the changed ranges of `page` and whether some part of it could not be read in one of the snapshots
+pub(crate) fn compare(
+    old: &mut Snapshot,
+    new: &mut Snapshot,
+    page: &VirtMemoryPage,
+) -> Result<(Vec<ChangedRange>, bool)> {
+    let mut changes: Vec<ChangedRange> = Vec::new();
+    let mut old_buf = vec![0; CHUNK_SIZE];
+    let mut new_buf = vec![0; CHUNK_SIZE];
+    let mut unreadable = false;
+    let mut address = page.from;
+    while address < page.to {
+        let len = CHUNK_SIZE.min((page.to - address) as usize);
+        let read = match (
+            old.read_partial(address, &mut old_buf[..len]),
+            new.read_partial(address, &mut new_buf[..len]),
+        ) {
+            (Ok(old_read), Ok(new_read)) => old_read.min(new_read),
+            (Err(err), _) | (_, Err(err)) if !err.is_recoverable() => return Err(err),
+            _ => 0,
+        };
+        if read == 0 {
+            // skip the memory page that is unreadable in one of the snapshots
+            unreadable = true;
+            address = (address + 1).next_multiple_of(page_size()).min(page.to);
+            continue;
+        }
+
+        let mut i = 0;
+        while i < read {
+            if old_buf[i] == new_buf[i] {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < read && old_buf[i] != new_buf[i] {
+                i += 1;
+            }
+            let (start, end) = (address + start as u64, address + i as u64);
+            match changes.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => changes.push(ChangedRange { start, end }),
+            }
+        }
+        address += read as u64;
+    }
+    Ok((changes, unreadable))
+}
@@ -0,0 +1,118 @@
+use std::{
+    fs::{create_dir, File},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Compression, Error, ProcessMemory, Result, Sink, SparseFile, TarWriter, VirtMemoryPage,
+};
+
+/// outcome of dumping a single page
+#[derive(Debug)]
+pub struct DumpedPage {
+    pub page: VirtMemoryPage,
+    pub file: Option<PathBuf>, // output file relative to the output directory
+    pub read: u64,             // number of bytes that could be read
+    pub unpopulated: u64,      // number of bytes skipped because they were never touched
+    pub holes: Vec<Range<u64>>, // address ranges that could not be read and were zero filled
+    pub error: Option<Error>,  // the first error that occurred while reading
+}
+
+impl DumpedPage {
+    /// copy `page` to `to`, unreadable parts are zero filled and recorded instead of returned
+    pub(crate) fn copy<S: Sink>(
+        memory: &mut ProcessMemory,
+        page: VirtMemoryPage,
+        to: S,
+    ) -> Result<Self> {
+        let copied = memory.copy_page(&page, to)?;
+        Ok(Self {
+            page,
+            file: None,
+            read: copied.read,
+            unpopulated: copied.unpopulated,
+            holes: copied.holes,
+            error: copied.error,
+        })
+    }
+}
+
+/// how the files of a dump directory and the members of a tar archive are named, recorded in the manifest
+pub const FILE_NAMING: &str = "ADDRESS_PERMS_PATH: start address as 16 hex digits, permissions like in \
+/proc/PID/maps and the pathname with every run of characters other than A-Z, a-z, 0-9, '.', '-' and '+' \
+replaced by '_' without leading and trailing '_', \
+shortened to its last 78 bytes, 'anon' for anonymous pages, followed by the extension \
+of the compression";
+
+/// maximum length of the sanitized pathname in a file name, so the whole name fits into a tar header
+const MAX_PATH_LEN: usize = 78;
+
+/// the name of the file a page is dumped to without the extension of the compression, see `FILE_NAMING`
+///
+/// The start address makes the name unique inside of a dump and the name can't contain `/`
+/// or be `.` or `..`, so it never refers to a file outside of the output directory.
+pub fn file_name(page: &VirtMemoryPage) -> String {
+    let mut path = String::new();
+    for c in page.file_path.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+') {
+            path.push(c);
+        } else if !path.ends_with('_') {
+            path.push('_');
+        }
+    }
+    let path = path.trim_matches('_');
+    let path = if path.is_empty() {
+        "anon"
+    } else {
+        // keep the end, the file name is more telling than the directories
+        &path[path.len().saturating_sub(MAX_PATH_LEN)..]
+    };
+    format!("{:016x}_{}_{path}", page.from, page.perms())
+}
+
+/// write each page to its own sparse file inside of `output_dir`, the manifest is not written
+///
+/// The files are named as described by `FILE_NAMING`. Compressed files are streamed through the encoder.
+pub fn dump_dir(
+    memory: &mut ProcessMemory,
+    pages: Vec<VirtMemoryPage>,
+    output_dir: &Path,
+    compression: Compression,
+) -> Result<Vec<DumpedPage>> {
+    if !output_dir.exists() {
+        create_dir(output_dir).map_err(Error::Output)?;
+    }
+
+    let mut dumped = Vec::new();
+    for page in pages {
+        let name = file_name(&page) + compression.extension();
+        let file = File::create(output_dir.join(&name)).map_err(Error::Output)?;
+        let mut encoder = compression
+            .encoder(SparseFile::new(file))
+            .map_err(Error::Output)?;
+        let mut result = DumpedPage::copy(memory, page, &mut encoder)?;
+        encoder.finish().map_err(Error::Output)?;
+        result.file = Some(PathBuf::from(name));
+        dumped.push(result);
+    }
+    Ok(dumped)
+}
+
+/// write each page as a member named as described by `FILE_NAMING` into `tar`, the manifest is not written
+pub fn dump_tar<S: Sink>(
+    memory: &mut ProcessMemory,
+    pages: Vec<VirtMemoryPage>,
+    tar: &mut TarWriter<S>,
+) -> Result<Vec<DumpedPage>> {
+    let mut dumped = Vec::new();
+    for page in pages {
+        let name = file_name(&page);
+        tar.start_member(&name, page.size())
+            .map_err(Error::Output)?;
+        let mut result = DumpedPage::copy(memory, page, &mut *tar)?;
+        result.file = Some(PathBuf::from(name));
+        dumped.push(result);
+    }
+    Ok(dumped)
+}
@@ -0,0 +1,118 @@
+use std::{fmt, io};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// a line of `/proc/PID/maps` that could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize, // 1-based line number, 0 if unknown
+    pub content: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(content: &str, reason: impl Into<String>) -> Self {
+        Self {
+            line: 0,
+            content: content.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line != 0 {
+            write!(f, "line {}: ", self.line)?;
+        }
+        write!(f, "{} ({:?})", self.reason, self.content)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Parse(ParseError),
+    InvalidFilter(String),
+    InvalidArgument(String),
+    NoSuchProcess(u32),
+    NoMatchingProcess(String), // no process matches the description
+    AmbiguousProcess(String, Vec<u32>), // multiple processes match the description
+    ProcessVanished(u32),      // the process existed but exited while being read
+    PermissionDenied,
+    NotMapped(u64),   // the address is not inside of any page
+    NotWritable(u64), // the address is inside of a page that is not writable
+    ShortRead {
+        address: u64,
+        expected: usize,
+        read: usize,
+    },
+    Io(io::Error),
+    Output(io::Error), // writing the dump failed
+}
+
+impl Error {
+    /// true if only reading a single range of memory failed and other ranges may still be readable
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::ShortRead { .. })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "Invalid memory map: {err}"),
+            Self::InvalidFilter(filter) => write!(f, "Invalid filter: {filter}"),
+            Self::InvalidArgument(reason) => write!(f, "Invalid argument: {reason}"),
+            Self::NoSuchProcess(pid) => write!(f, "Process with PID {pid} does not exist"),
+            Self::NoMatchingProcess(target) => write!(f, "No process matches {target}"),
+            Self::AmbiguousProcess(target, pids) => write!(
+                f,
+                "Multiple processes match {target}: {}",
+                pids.iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::ProcessVanished(pid) => write!(f, "Process with PID {pid} exited"),
+            Self::PermissionDenied => write!(f, "Permission denied"),
+            Self::NotMapped(address) => write!(f, "Address {address:#x} is not mapped"),
+            Self::NotWritable(address) => {
+                write!(f, "Address {address:#x} is not inside of a writable page")
+            }
+            Self::ShortRead {
+                address,
+                expected,
+                read,
+            } => write!(
+                f,
+                "Short read at {address:#x}: expected {expected} bytes, got {read}"
+            ),
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Output(err) => write!(f, "Error while writing output: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) | Self::Output(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            _ => Self::Io(err),
+        }
+    }
+}
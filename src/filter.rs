@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use crate::{Error, Mode, SmapsField, VirtMemoryPage, MODE_EXEC, MODE_READ, MODE_WRITE};
+
+/// selects memory pages by permissions, pathname, address, size and memory usage
+///
+/// A filter can be parsed from a comma separated list of conditions that all have to match,
+/// e.g. `perms=r-x,path=*libc*`. Supported conditions are:
+/// - `perms=MASK`: `r`, `w`, `x`, `s` and `p` must be set, `-` must not be set, `?` is ignored
+/// - `path=GLOB`: pathname matches the glob (`*` and `?`), `path=` matches anonymous pages
+/// - `anon`: same as `path=`
+/// - `addr=FROM-TO` or `addr=ADDR`: page overlaps the hex address range or contains the address
+/// - `min-size=SIZE`, `max-size=SIZE`: size in bytes, `K`, `M` and `G` suffixes are allowed
+/// - `min-FIELD=SIZE`, `max-FIELD=SIZE`: memory usage from `/proc/PID/smaps` where `FIELD` is one of
+///   `rss`, `pss`, `shared-clean`, `shared-dirty`, `private-clean`, `private-dirty`, `swap` and
+///   `anon-huge-pages`
+/// - `dirty`: same as `min-private-dirty=1`, pages modified by the process
+/// - `swapped`: same as `min-swap=1`
+/// - `flag=FLAG`: the page has the two letter `VmFlags` entry, e.g. `flag=dd`
+/// - `!CONDITION`: negates a condition
+///
+/// Conditions on the memory usage never match pages without `stats`, see `needs_smaps`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    All,
+    Perms {
+        set: Mode,
+        unset: Mode,
+        shared: Option<bool>,
+    },
+    Path(String),
+    Address {
+        from: u64,
+        to: u64,
+    },
+    MinSize(u64),
+    MaxSize(u64),
+    MinStat(SmapsField, u64),
+    MaxStat(SmapsField, u64),
+    VmFlag(String),
+    Not(Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    /// the filter used if nothing else was selected: readable and writable pages
+    pub fn readable_writable() -> Self {
+        Self::Perms {
+            set: MODE_READ | MODE_WRITE,
+            unset: 0,
+            shared: None,
+        }
+    }
+
+    /// matches if any of `filters` matches, or everything if `filters` is empty
+    pub fn any(filters: Vec<Filter>) -> Self {
+        if filters.is_empty() {
+            Self::All
+        } else {
+            Self::Or(filters)
+        }
+    }
+
+    pub fn matches(&self, page: &VirtMemoryPage) -> bool {
+        match self {
+            Self::All => true,
+            Self::Perms { set, unset, shared } => {
+                page.mode & set == *set
+                    && page.mode & unset == 0
+                    && shared.is_none_or(|s| s == page.shared)
+            }
+            Self::Path(glob) => glob_matches(glob, &page.file_path),
+            Self::Address { from, to } => page.from < *to && *from < page.to,
+            Self::MinSize(size) => page.size() >= *size,
+            Self::MaxSize(size) => page.size() <= *size,
+            Self::MinStat(field, size) => {
+                page.stats.as_ref().is_some_and(|s| s.get(*field) >= *size)
+            }
+            Self::MaxStat(field, size) => {
+                page.stats.as_ref().is_some_and(|s| s.get(*field) <= *size)
+            }
+            Self::VmFlag(flag) => page.stats.as_ref().is_some_and(|s| s.has_flag(flag)),
+            Self::Not(filter) => !filter.matches(page),
+            Self::And(filters) => filters.iter().all(|f| f.matches(page)),
+            Self::Or(filters) => filters.iter().any(|f| f.matches(page)),
+        }
+    }
+
+    /// true if the filter uses the memory usage of pages, so they have to be read from `/proc/PID/smaps`
+    pub fn needs_smaps(&self) -> bool {
+        match self {
+            Self::MinStat(..) | Self::MaxStat(..) | Self::VmFlag(_) => true,
+            Self::Not(filter) => filter.needs_smaps(),
+            Self::And(filters) | Self::Or(filters) => filters.iter().any(Self::needs_smaps),
+            _ => false,
+        }
+    }
+
+    fn parse_condition(condition: &str) -> Result<Self, Error> {
+        if let Some(condition) = condition.strip_prefix('!') {
+            return Ok(Self::Not(Box::new(Self::parse_condition(condition)?)));
+        }
+        let invalid = || Error::InvalidFilter(condition.to_string());
+        let (key, value) = condition.split_once('=').unwrap_or((condition, ""));
+        Ok(match key {
+            "all" => Self::All,
+            "anon" => Self::Path(String::new()),
+            "perms" => {
+                let (mut set, mut unset, mut shared) = (0, 0, None);
+                for (i, char) in value.chars().enumerate() {
+                    let mode = [MODE_READ, MODE_WRITE, MODE_EXEC].get(i).copied();
+                    match (char, mode) {
+                        ('r', Some(MODE_READ))
+                        | ('w', Some(MODE_WRITE))
+                        | ('x', Some(MODE_EXEC)) => set |= mode.unwrap(),
+                        ('-', Some(mode)) => unset |= mode,
+                        ('s', None) => shared = Some(true),
+                        ('p', None) => shared = Some(false),
+                        ('?', _) => (),
+                        _ => return Err(invalid()),
+                    }
+                }
+                Self::Perms { set, unset, shared }
+            }
+            "path" => Self::Path(value.to_string()),
+            "addr" => {
+                let parse = |s: &str| {
+                    u64::from_str_radix(s.trim_start_matches("0x"), 16).map_err(|_| invalid())
+                };
+                match value.split_once('-') {
+                    Some((from, to)) => Self::Address {
+                        from: parse(from)?,
+                        to: parse(to)?,
+                    },
+                    None => {
+                        let address = parse(value)?;
+                        Self::Address {
+                            from: address,
+                            to: address.saturating_add(1),
+                        }
+                    }
+                }
+            }
+            "min-size" => Self::MinSize(parse_size(value).ok_or_else(invalid)?),
+            "max-size" => Self::MaxSize(parse_size(value).ok_or_else(invalid)?),
+            "dirty" => Self::MinStat(SmapsField::PrivateDirty, 1),
+            "swapped" => Self::MinStat(SmapsField::Swap, 1),
+            "flag" if value.len() == 2 => Self::VmFlag(value.to_string()),
+            _ => {
+                let stat = |name: &str| SmapsField::from_name(name).ok_or_else(invalid);
+                let size = || parse_size(value).ok_or_else(invalid);
+                if let Some(name) = key.strip_prefix("min-") {
+                    Self::MinStat(stat(name)?, size()?)
+                } else if let Some(name) = key.strip_prefix("max-") {
+                    Self::MaxStat(stat(name)?, size()?)
+                } else {
+                    return Err(invalid());
+                }
+            }
+        })
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    /// parse a comma separated list of conditions that all have to match
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut conditions = s
+            .split(',')
+            .filter(|c| !c.is_empty())
+            .map(Self::parse_condition)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(match conditions.len() {
+            0 => Self::All,
+            1 => conditions.remove(0),
+            _ => Self::And(conditions),
+        })
+    }
+}
+
+/// parse a size like `4096`, `0x1000`, `64K`, `2M` or `1G`
+pub(crate) fn parse_size(s: &str) -> Option<u64> {
+    if let Some(hex) = s.strip_prefix("0x") {
+        return u64::from_str_radix(hex, 16).ok();
+    }
+    let (number, factor) = match s.char_indices().last()? {
+        (i, 'k' | 'K') => (&s[..i], 1 << 10),
+        (i, 'm' | 'M') => (&s[..i], 1 << 20),
+        (i, 'g' | 'G') => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    number.parse::<u64>().ok()?.checked_mul(factor)
+}
+
+/// match `text` against a glob where `*` matches any sequence and `?` any single character
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob = glob.chars().collect::<Vec<char>>();
+    let text = text.chars().collect::<Vec<char>>();
+    let (mut g, mut t) = (0, 0);
+    let mut backtrack = None; // position of the last '*' in glob and text
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, t));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, start)) => {
+                    // let the last '*' consume one more character
+                    g = star + 1;
+                    t = start + 1;
+                    backtrack = Some((star, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
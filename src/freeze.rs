@@ -0,0 +1,237 @@
+use std::{
+    io, ptr,
+    str::FromStr,
+    sync::atomic::{AtomicI32, Ordering},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::{process::vanished_or, Error, Process, Result};
+
+/// how long to wait until all threads of a process stopped
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// PID of the process stopped with SIGSTOP that has to be continued if we are interrupted, 0 if none
+static STOPPED_PID: AtomicI32 = AtomicI32::new(0);
+
+/// how a process should be stopped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FreezeMethod {
+    /// send SIGSTOP and SIGCONT, the process is continued by a signal handler if we get interrupted
+    #[default]
+    Signal,
+    /// seize and interrupt every thread with ptrace, the kernel resumes the threads if we exit
+    Ptrace,
+}
+
+impl FromStr for FreezeMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "signal" => Ok(Self::Signal),
+            "ptrace" => Ok(Self::Ptrace),
+            _ => Err(Error::InvalidArgument(format!(
+                "unknown freeze method '{s}'"
+            ))),
+        }
+    }
+}
+
+/// a stopped process that is resumed when this is dropped
+pub struct FrozenProcess {
+    pid: u32,
+    method: FreezeMethod,
+    seized: Vec<u32>,  // threads attached with ptrace
+    was_stopped: bool, // the process was stopped before, so it must not be continued
+}
+
+impl FrozenProcess {
+    pub(crate) fn new(process: &Process, method: FreezeMethod) -> Result<Self> {
+        let mut frozen = Self {
+            pid: process.pid(),
+            method,
+            seized: Vec::new(),
+            was_stopped: false,
+        };
+        match method {
+            FreezeMethod::Signal => {
+                frozen.was_stopped = process.stat()?.state == 'T';
+                if !frozen.was_stopped {
+                    install_interrupt_handler();
+                    STOPPED_PID.store(frozen.pid as i32, Ordering::SeqCst);
+                    kill(frozen.pid, libc::SIGSTOP)?;
+                    frozen.wait_until_stopped(process)?;
+                }
+            }
+            FreezeMethod::Ptrace => {
+                // threads may be created while we attach, so repeat until no new thread showed up
+                loop {
+                    let threads = process.threads()?;
+                    let new = threads
+                        .into_iter()
+                        .filter(|tid| !frozen.seized.contains(tid))
+                        .collect::<Vec<u32>>();
+                    if new.is_empty() {
+                        break;
+                    }
+                    for tid in new {
+                        match seize(tid) {
+                            Ok(()) => frozen.seized.push(tid),
+                            // the thread exited in the meantime
+                            Err(err) if err.raw_os_error() == Some(libc::ESRCH) => (),
+                            Err(err) => return Err(vanished_or(frozen.pid, err)),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(frozen)
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn method(&self) -> FreezeMethod {
+        self.method
+    }
+
+    /// the threads that are attached with ptrace, empty if `FreezeMethod::Signal` is used
+    pub fn seized_threads(&self) -> &[u32] {
+        &self.seized
+    }
+
+    /// resume the process, errors are ignored if the process is resumed by dropping it
+    pub fn resume(mut self) -> Result<()> {
+        self.resume_inner()
+    }
+
+    fn resume_inner(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        for tid in self.seized.drain(..) {
+            // SAFETY: PTRACE_DETACH doesn't access memory of our process
+            if unsafe { libc::ptrace(libc::PTRACE_DETACH, tid as libc::pid_t, 0, 0) } < 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(libc::ESRCH) {
+                    result = Err(err.into());
+                }
+            }
+        }
+        if self.method == FreezeMethod::Signal && !self.was_stopped {
+            // only resume once
+            self.was_stopped = true;
+            STOPPED_PID.store(0, Ordering::SeqCst);
+            match kill(self.pid, libc::SIGCONT) {
+                Err(Error::ProcessVanished(_)) => (),
+                Err(err) => result = Err(err),
+                Ok(()) => (),
+            }
+        }
+        result
+    }
+
+    /// wait until all threads are in the stopped state
+    fn wait_until_stopped(&self, process: &Process) -> Result<()> {
+        let start = Instant::now();
+        loop {
+            let mut stopped = true;
+            for tid in process.threads()? {
+                if let Ok(stat) = process.thread_stat(tid) {
+                    stopped &= stat.state == 'T';
+                }
+            }
+            if stopped {
+                return Ok(());
+            }
+            if start.elapsed() > STOP_TIMEOUT {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "process did not stop",
+                )));
+            }
+            sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+impl Drop for FrozenProcess {
+    fn drop(&mut self) {
+        let _ = self.resume_inner();
+    }
+}
+
+fn kill(pid: u32, signal: libc::c_int) -> Result<()> {
+    // SAFETY: kill doesn't access memory
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } < 0 {
+        return Err(vanished_or(pid, io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// attach to the thread `tid` with ptrace and stop it
+///
+/// Fails with `TimedOut` if the thread doesn't stop within `STOP_TIMEOUT`, e.g. because it's blocked in
+/// uninterruptible sleep. It's detached then as far as possible, otherwise the kernel detaches it when we exit.
+pub(crate) fn seize(tid: u32) -> io::Result<()> {
+    let tid = tid as libc::pid_t;
+    // SAFETY: PTRACE_SEIZE and PTRACE_INTERRUPT don't access memory of our process
+    unsafe {
+        if libc::ptrace(libc::PTRACE_SEIZE, tid, 0, 0) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if libc::ptrace(libc::PTRACE_INTERRUPT, tid, 0, 0) < 0 {
+            let err = io::Error::last_os_error();
+            libc::ptrace(libc::PTRACE_DETACH, tid, 0, 0);
+            return Err(err);
+        }
+    }
+    let start = Instant::now();
+    let mut status = 0;
+    loop {
+        // SAFETY: status is a valid pointer
+        match unsafe { libc::waitpid(tid, &mut status, libc::__WALL | libc::WNOHANG) } {
+            0 if start.elapsed() > STOP_TIMEOUT => {
+                // SAFETY: PTRACE_DETACH doesn't access memory of our process
+                unsafe { libc::ptrace(libc::PTRACE_DETACH, tid, 0, 0) };
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "thread did not stop",
+                ));
+            }
+            0 => sleep(Duration::from_millis(1)),
+            result if result < 0 => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// continue the process stopped with SIGSTOP and terminate with the default action of the signal
+extern "C" fn on_interrupt(signal: libc::c_int) {
+    let pid = STOPPED_PID.swap(0, Ordering::SeqCst);
+    // SAFETY: kill, signal and raise are async-signal-safe
+    unsafe {
+        if pid != 0 {
+            libc::kill(pid, libc::SIGCONT);
+        }
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+fn install_interrupt_handler() {
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT] {
+        // SAFETY: the handler only calls async-signal-safe functions
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_interrupt as *const () as libc::sighandler_t;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Read the memory of a running process on linux.
+//!
+//! The regions of a process are listed by parsing `/proc/PID/maps` and read
+//! through `/proc/PID/mem`.
+
+mod compress;
+mod copy;
+mod coredump;
+mod diff;
+mod dump;
+mod error;
+mod filter;
+mod freeze;
+mod manifest;
+mod page;
+mod pagemap;
+mod process;
+mod query;
+mod reader;
+mod restore;
+mod scan;
+mod search;
+mod smaps;
+mod snapshot;
+mod sparse;
+mod symbols;
+mod tar;
+mod thread;
+mod util;
+mod writer;
+
+pub use compress::{Compression, Encoder};
+pub use copy::Copied;
+pub use coredump::write_core;
+pub use diff::{diff, ChangedRange, ChangedRegion, Diff, Mapping};
+pub use dump::{dump_dir, dump_tar, file_name, DumpedPage, FILE_NAMING};
+pub use error::{Error, ParseError, Result};
+pub use filter::Filter;
+pub use freeze::{FreezeMethod, FrozenProcess};
+pub use manifest::{
+    Hole, Index, IndexEntry, Manifest, ProcessInfo, Region, Stack, ThreadInfo, INDEX_FILE_NAME,
+    MANIFEST_FILE_NAME,
+};
+pub use page::{Mode, VirtMemoryPage, MODE_EXEC, MODE_READ, MODE_WRITE};
+pub use pagemap::{Pagemap, PagemapEntry};
+pub use process::{Process, ProcessMemory, Stat};
+pub use query::ProcessQuery;
+pub use reader::{AutoReader, Backend, MemoryReader, ProcMemReader, VmReadvReader};
+pub use restore::{restore, RestoredRegion, Skipped};
+pub use scan::{Candidate, Condition, Scan, ValueType};
+pub use search::{search, Match, Pattern};
+pub use smaps::{Smaps, SmapsField};
+pub use snapshot::{DumpReader, Memory, Snapshot};
+pub use sparse::{Sink, SparseFile, ZeroFill};
+pub use symbols::{elf_symbols, Symbol};
+pub use tar::TarWriter;
+pub use thread::{Registers, Thread};
+pub use util::{group_by, ncopy, page_size};
+pub use writer::{check_writable, MemoryWriter};
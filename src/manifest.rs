@@ -0,0 +1,321 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    snapshot::DumpFile,
+    tar::{self, Member},
+    Compression, DumpedPage, Error, Mapping, Process, Registers, Result, Smaps, Thread,
+    VirtMemoryPage, FILE_NAMING,
+};
+
+/// file name of the manifest inside of a dump directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// file name of the index of a dump of multiple processes
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+/// machine-readable description of a dump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub process: ProcessInfo,
+    pub format: String, // `dir`, `core` or `tar`
+    #[serde(default)]
+    pub compression: Compression, // of the region files, the core file or the tar archive
+    #[serde(default)]
+    pub file_naming: String, // how the files of the regions are named, see `FILE_NAMING`
+    pub regions: Vec<Region>,
+    #[serde(default)]
+    pub threads: Vec<ThreadInfo>,
+    #[serde(default)]
+    pub mappings: Vec<Mapping>, // all mappings of the process at the time of the dump, dumped or not
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub comm: String,
+    pub cmdline: Vec<String>,
+    pub exe: Option<PathBuf>, // `None` if the link could not be read, e.g. for kernel threads
+    pub timestamp: u64,       // seconds since the unix epoch when the dump was created
+}
+
+/// machine-readable description of a dump of multiple processes, every process has its own dump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Index {
+    pub timestamp: u64, // seconds since the unix epoch when the dump was started
+    pub processes: Vec<IndexEntry>, // every process comes before its children
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub pid: u32,
+    pub ppid: u32,
+    pub comm: String,
+    pub cmdline: Vec<String>,
+    pub children: Vec<u32>, // the children that are part of the index as well
+    pub dump: Option<PathBuf>, // the dump of the process relative to the index, `None` if it failed
+    pub error: Option<String>, // why the process could not be dumped
+}
+
+/// a dumped page, addresses and offsets are hex strings like `0x7f0000001000`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Region {
+    #[serde(with = "hex")]
+    pub start: u64,
+    #[serde(with = "hex")]
+    pub end: u64,
+    pub perms: String, // like in `/proc/PID/maps`, e.g. `r-xp`
+    #[serde(with = "hex")]
+    pub offset: u64,
+    pub device: String, // `major:minor` in hex
+    pub inode: u64,
+    pub path: String,
+    pub file: Option<PathBuf>, // output file relative to the dump directory or tar member
+    pub read: u64,             // number of bytes that could be read
+    pub unpopulated: u64,      // number of bytes that were never touched and are stored as zeros
+    pub holes: Vec<Hole>,      // unreadable ranges that were zero filled
+    pub error: Option<String>, // the first error that occurred while reading
+    #[serde(default)]
+    pub stats: Option<Smaps>, // memory usage at the time of the dump from `/proc/PID/smaps`
+}
+
+/// a thread of the process at the time of the dump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    pub state: char,                  // like in `/proc/PID/stat`, e.g. `S` or `R`
+    pub registers: Option<Registers>, // `None` if they could not be read
+    pub stack: Option<Stack>,         // the mapping containing the stack pointer
+    pub error: Option<String>,        // why the registers could not be read
+}
+
+/// the address range of the mapping a thread uses as stack
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stack {
+    #[serde(with = "hex")]
+    pub start: u64,
+    #[serde(with = "hex")]
+    pub end: u64,
+}
+
+/// an address range that could not be read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hole {
+    #[serde(with = "hex")]
+    pub start: u64,
+    #[serde(with = "hex")]
+    pub end: u64,
+}
+
+impl Manifest {
+    /// describe a dump of `process`, the regions are sorted by address
+    ///
+    /// `pages` are all mappings of the process at the time the threads were captured, they are recorded
+    /// as `mappings` and the stacks of `threads` are looked up in them.
+    pub fn new(
+        process: &Process,
+        format: &str,
+        compression: Compression,
+        dumped: &[DumpedPage],
+        threads: &[Thread],
+        pages: &[VirtMemoryPage],
+    ) -> Result<Self> {
+        let mut regions = dumped.iter().map(Region::from).collect::<Vec<Region>>();
+        regions.sort_by_key(|r| r.start);
+        let threads = threads
+            .iter()
+            .map(|thread| ThreadInfo {
+                tid: thread.tid,
+                name: thread.name.clone(),
+                state: thread.stat.state,
+                registers: thread.registers.clone(),
+                stack: thread.stack(pages).map(|page| Stack {
+                    start: page.from,
+                    end: page.to,
+                }),
+                error: thread.error.as_ref().map(|err| err.to_string()),
+            })
+            .collect();
+        Ok(Self {
+            process: ProcessInfo {
+                pid: process.pid(),
+                comm: process.comm()?,
+                cmdline: process.cmdline()?,
+                exe: process.exe().ok(),
+                timestamp: now(),
+            },
+            format: format.to_string(),
+            compression,
+            file_naming: FILE_NAMING.to_string(),
+            regions,
+            threads,
+            mappings: pages.iter().map(Mapping::from).collect(),
+        })
+    }
+
+    /// the manifest of a dump directory, of a core file, which is stored next to it,
+    /// or of a tar archive, which contains it
+    pub fn of_dump(dump: &Path) -> Result<Self> {
+        if dump.is_dir() {
+            return Self::read(&dump.join(MANIFEST_FILE_NAME));
+        }
+        if core_manifest_path(dump).exists() {
+            return Self::read(&core_manifest_path(dump));
+        }
+        let not_a_dump = || Error::InvalidArgument(format!("{dump:?} is not a dump"));
+        let mut tar = DumpFile::open(dump, Compression::detect(dump)?)?;
+        let members = tar::members(&mut tar).map_err(|_| not_a_dump())?;
+        Self::from_tar(&mut tar, &members)?.ok_or_else(not_a_dump)
+    }
+
+    /// the manifest stored in a tar archive, `None` if there is none
+    pub(crate) fn from_tar(tar: &mut DumpFile, members: &[Member]) -> Result<Option<Self>> {
+        let Some(member) = members.iter().find(|m| m.name == MANIFEST_FILE_NAME) else {
+            return Ok(None);
+        };
+        let mut buf = vec![0; member.size as usize];
+        tar.read_exact_at(&mut buf, member.offset)?;
+        serde_json::from_slice(&buf)
+            .map(Some)
+            .map_err(|err| Error::InvalidArgument(format!("invalid manifest: {err}")))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        serde_json::from_reader(file)
+            .map_err(|err| Error::InvalidArgument(format!("invalid manifest {path:?}: {err}")))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_file(path, &self.to_json())
+    }
+
+    /// the manifest as pretty printed JSON with a trailing newline
+    pub fn to_json(&self) -> Vec<u8> {
+        let mut json =
+            serde_json::to_vec_pretty(self).expect("a manifest can always be serialized");
+        json.push(b'\n');
+        json
+    }
+}
+
+impl Index {
+    /// describe `processes` before they are dumped, every process has to come before its children
+    ///
+    /// Processes that exited already are described as far as possible, dumping them will fail.
+    pub fn new(processes: &[Process]) -> Self {
+        let mut entries: Vec<IndexEntry> = Vec::new();
+        for process in processes {
+            let ppid = process.stat().map_or(0, |stat| stat.ppid);
+            if let Some(parent) = entries.iter_mut().find(|e| e.pid == ppid) {
+                parent.children.push(process.pid());
+            }
+            entries.push(IndexEntry {
+                pid: process.pid(),
+                ppid,
+                comm: process.comm().unwrap_or_default(),
+                cmdline: process.cmdline().unwrap_or_default(),
+                children: Vec::new(),
+                dump: None,
+                error: None,
+            });
+        }
+        Self {
+            timestamp: now(),
+            processes: entries,
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        serde_json::from_reader(file)
+            .map_err(|err| Error::InvalidArgument(format!("invalid index {path:?}: {err}")))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut json = serde_json::to_vec_pretty(self).expect("an index can always be serialized");
+        json.push(b'\n');
+        write_file(path, &json)
+    }
+}
+
+fn write_file(path: &Path, content: &[u8]) -> Result<()> {
+    let mut file = BufWriter::new(File::create(path).map_err(Error::Output)?);
+    file.write_all(content).map_err(Error::Output)?;
+    file.flush().map_err(Error::Output)
+}
+
+/// seconds since the unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// path of the manifest of a core file
+pub(crate) fn core_manifest_path(core: &Path) -> PathBuf {
+    let mut path = core.as_os_str().to_os_string();
+    path.push(".json");
+    PathBuf::from(path)
+}
+
+impl Region {
+    /// the page this region was dumped from
+    pub fn page(&self) -> Result<VirtMemoryPage> {
+        let mut page = VirtMemoryPage::from_line(&format!(
+            "{:x}-{:x} {} {:x} {} {} {}",
+            self.start, self.end, self.perms, self.offset, self.device, self.inode, self.path
+        ))?;
+        page.stats = self.stats.clone();
+        Ok(page)
+    }
+}
+
+impl From<&DumpedPage> for Region {
+    fn from(dumped: &DumpedPage) -> Self {
+        let page = &dumped.page;
+        Self {
+            start: page.from,
+            end: page.to,
+            perms: page.perms(),
+            offset: page.offset,
+            device: format!("{:02x}:{:02x}", page.device.0, page.device.1),
+            inode: page.inode,
+            path: page.file_path.clone(),
+            file: dumped.file.clone(),
+            read: dumped.read,
+            unpopulated: dumped.unpopulated,
+            holes: dumped
+                .holes
+                .iter()
+                .map(|hole| Hole {
+                    start: hole.start,
+                    end: hole.end,
+                })
+                .collect(),
+            error: dumped.error.as_ref().map(|err| err.to_string()),
+            stats: page.stats.clone(),
+        }
+    }
+}
+
+/// (de)serialize a `u64` as hex string with `0x` prefix
+pub(crate) mod hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:#x}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        u64::from_str_radix(s.trim_start_matches("0x"), 16).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+
+use crate::{ParseError, Smaps};
+
+pub type Mode = u8;
+pub const MODE_EXEC: u8 = 1;
+pub const MODE_WRITE: u8 = 2;
+pub const MODE_READ: u8 = 4;
+
+/// a single mapping of the virtual memory of a process, one line of `/proc/PID/maps`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VirtMemoryPage {
+    pub from: u64, // page starts at this address
+    pub to: u64,   // page ends at this address
+    pub mode: Mode,
+    pub shared: bool,         // 's' instead of 'p' in the permissions
+    pub offset: u64,          // offset of the mapping in the mapped file
+    pub device: (u32, u32),   // major and minor number of the device the file lives on
+    pub inode: u64,           // inode of the mapped file, 0 for anonymous mappings
+    pub file_path: String,    // path to file, '[heap]', '[stack]', ... or emtpy
+    pub stats: Option<Smaps>, // memory usage, only set if the page was read from `/proc/PID/smaps`
+}
+
+impl VirtMemoryPage {
+    /// parse a single line of `/proc/PID/maps`
+    pub fn from_line(line: &str) -> Result<Self, ParseError> {
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 5 {
+            return Err(ParseError::new(line, "too few fields"));
+        }
+        let mut mode = 0;
+        let mut shared = false;
+        for char in parts[1].chars() {
+            match char {
+                'r' => mode |= MODE_READ,
+                'w' => mode |= MODE_WRITE,
+                'x' => mode |= MODE_EXEC,
+                'p' => shared = false,
+                's' => shared = true,
+                '-' => (),
+                _ => {
+                    return Err(ParseError::new(
+                        line,
+                        format!("invalid permission character '{char}'"),
+                    ))
+                }
+            }
+        }
+
+        let parse_hex = |value, name| {
+            u64::from_str_radix(value, 16)
+                .map_err(|_| ParseError::new(line, format!("invalid {name} '{value}'")))
+        };
+        let Some((from, to)) = parts[0].split_once('-') else {
+            return Err(ParseError::new(line, "invalid address range"));
+        };
+        let Some((major, minor)) = parts[3].split_once(':') else {
+            return Err(ParseError::new(line, "invalid device"));
+        };
+        Ok(Self {
+            from: parse_hex(from, "address")?,
+            to: parse_hex(to, "address")?,
+            mode,
+            shared,
+            offset: parse_hex(parts[2], "offset")?,
+            device: (
+                parse_hex(major, "device")? as u32,
+                parse_hex(minor, "device")? as u32,
+            ),
+            inode: parts[4]
+                .parse()
+                .map_err(|_| ParseError::new(line, format!("invalid inode '{}'", parts[4])))?,
+            file_path: if parts.len() > 5 {
+                parts[5..].join(" ")
+            } else {
+                String::new()
+            },
+            stats: None,
+        })
+    }
+
+    /// parse the whole content of `/proc/PID/maps`
+    pub fn parse_maps(maps: &str) -> Result<Vec<Self>, ParseError> {
+        maps.split('\n')
+            .enumerate()
+            .filter(|(_, l)| !l.is_empty()) // empty lines should not be considered
+            .map(|(i, l)| {
+                Self::from_line(l).map_err(|mut err| {
+                    err.line = i + 1;
+                    err
+                })
+            })
+            .collect()
+    }
+
+    /// size of the page in bytes
+    pub fn size(&self) -> u64 {
+        self.to - self.from
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.mode & MODE_READ != 0
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.mode & MODE_WRITE != 0
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.mode & MODE_EXEC != 0
+    }
+
+    /// true if the page has no pathname
+    pub fn is_anonymous(&self) -> bool {
+        self.file_path.is_empty()
+    }
+
+    /// true if the mapped file was deleted or replaced after it was mapped
+    pub fn is_deleted(&self) -> bool {
+        self.file_path.ends_with(" (deleted)")
+    }
+
+    /// permissions in the format of `/proc/PID/maps`, e.g. `r-xp`
+    pub fn perms(&self) -> String {
+        let flag = |set, c| if set { c } else { '-' };
+        [
+            flag(self.is_readable(), 'r'),
+            flag(self.is_writable(), 'w'),
+            flag(self.is_executable(), 'x'),
+            if self.shared { 's' } else { 'p' },
+        ]
+        .iter()
+        .collect()
+    }
+}
+
+/// formats the page like a line of `/proc/PID/maps`
+impl fmt::Display for VirtMemoryPage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:x}-{:x} {} {:08x} {:02x}:{:02x} {} {}",
+            self.from,
+            self.to,
+            self.perms(),
+            self.offset,
+            self.device.0,
+            self.device.1,
+            self.inode,
+            self.file_path
+        )
+    }
+}
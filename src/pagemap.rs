@@ -0,0 +1,49 @@
+use std::{fs::File, io, os::unix::fs::FileExt, path::Path};
+
+use crate::page_size;
+
+/// the page table of a process exposed in `/proc/PID/pagemap`
+pub struct Pagemap {
+    file: File,
+}
+
+/// entry of the pagemap describing a single page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PagemapEntry(pub u64);
+
+impl PagemapEntry {
+    pub fn present(&self) -> bool {
+        self.0 & 1 << 63 != 0
+    }
+
+    pub fn swapped(&self) -> bool {
+        self.0 & 1 << 62 != 0
+    }
+
+    /// true if the page is in memory or swapped out, false e.g. for never touched anonymous memory
+    pub fn populated(&self) -> bool {
+        self.present() || self.swapped()
+    }
+}
+
+impl Pagemap {
+    /// `proc_path` is the `/proc/PID` directory of the process
+    pub fn open(proc_path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: File::open(proc_path.join("pagemap"))?,
+        })
+    }
+
+    /// entries of all pages in the address range `from..to`
+    pub fn entries(&self, from: u64, to: u64) -> io::Result<Vec<PagemapEntry>> {
+        let page_size = page_size();
+        let first = from / page_size;
+        let count = (to.div_ceil(page_size) - first) as usize;
+        let mut buf = vec![0; count * 8];
+        self.file.read_exact_at(&mut buf, first * 8)?;
+        Ok(buf
+            .chunks_exact(8)
+            .map(|entry| PagemapEntry(u64::from_ne_bytes(entry.try_into().unwrap())))
+            .collect())
+    }
+}
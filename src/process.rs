@@ -0,0 +1,353 @@
+use std::{
+    fs::{read, read_dir, read_link, read_to_string},
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    AutoReader, Backend, Error, FreezeMethod, FrozenProcess, MemoryReader, Pagemap, ProcMemReader,
+    ProcessQuery, Result, VirtMemoryPage, VmReadvReader,
+};
+
+/// mount point of the cgroup hierarchy
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// a running process identified by its PID
+pub struct Process {
+    pid: u32,
+    path: PathBuf, // /proc/PID
+}
+
+impl Process {
+    /// fails with `Error::NoSuchProcess` if there is no process with this PID
+    pub fn new(pid: u32) -> Result<Self> {
+        let path = PathBuf::from(format!("/proc/{pid}"));
+        if !path.exists() {
+            return Err(Error::NoSuchProcess(pid));
+        }
+        Ok(Self { pid, path })
+    }
+
+    /// all processes that are currently running
+    pub fn all() -> Result<Vec<Self>> {
+        let mut processes = read_dir("/proc")?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .filter_map(|pid| Self::new(pid).ok())
+            .collect::<Vec<Self>>();
+        processes.sort_by_key(|p| p.pid);
+        Ok(processes)
+    }
+
+    /// all processes whose `comm` is `name`
+    pub fn find_by_name(name: &str) -> Result<Vec<Self>> {
+        ProcessQuery {
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+        .find()
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// the `/proc/PID` directory of the process
+    pub fn proc_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// list all memory pages of the process by parsing `/proc/PID/maps`
+    pub fn maps(&self) -> Result<Vec<VirtMemoryPage>> {
+        let maps = read_to_string(self.path.join("maps")).map_err(|err| self.io_error(err))?;
+        Ok(VirtMemoryPage::parse_maps(&maps)?)
+    }
+
+    /// list all memory pages of the process including their memory usage by parsing `/proc/PID/smaps`
+    ///
+    /// Slower than `maps` because the kernel walks the page tables of every mapping.
+    pub fn smaps(&self) -> Result<Vec<VirtMemoryPage>> {
+        let smaps = read_to_string(self.path.join("smaps")).map_err(|err| self.io_error(err))?;
+        Ok(VirtMemoryPage::parse_smaps(&smaps)?)
+    }
+
+    /// name of the executable as shown in `/proc/PID/comm`
+    pub fn comm(&self) -> Result<String> {
+        let comm = read_to_string(self.path.join("comm")).map_err(|err| self.io_error(err))?;
+        Ok(comm.trim_end_matches('\n').to_string())
+    }
+
+    /// command line arguments of the process
+    pub fn cmdline(&self) -> Result<Vec<String>> {
+        let cmdline = read(self.path.join("cmdline")).map_err(|err| self.io_error(err))?;
+        Ok(cmdline
+            .split(|&b| b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect())
+    }
+
+    /// path of the executable of the process
+    pub fn exe(&self) -> Result<PathBuf> {
+        read_link(self.path.join("exe")).map_err(|err| self.io_error(err))
+    }
+
+    /// status information of the process from `/proc/PID/stat`
+    pub fn stat(&self) -> Result<Stat> {
+        let stat = read_to_string(self.path.join("stat")).map_err(|err| self.io_error(err))?;
+        Stat::parse(&stat).ok_or_else(|| Error::InvalidArgument(format!("invalid stat: {stat}")))
+    }
+
+    /// status information of the thread `tid` from `/proc/PID/task/TID/stat`
+    pub fn thread_stat(&self, tid: u32) -> Result<Stat> {
+        let stat = read_to_string(self.path.join(format!("task/{tid}/stat")))
+            .map_err(|err| self.io_error(err))?;
+        Stat::parse(&stat).ok_or_else(|| Error::InvalidArgument(format!("invalid stat: {stat}")))
+    }
+
+    /// name of the thread `tid` as shown in `/proc/PID/task/TID/comm`
+    pub fn thread_comm(&self, tid: u32) -> Result<String> {
+        let comm = read_to_string(self.path.join(format!("task/{tid}/comm")))
+            .map_err(|err| self.io_error(err))?;
+        Ok(comm.trim_end_matches('\n').to_string())
+    }
+
+    /// real user and group id of the process
+    pub fn uid_gid(&self) -> Result<(u32, u32)> {
+        let status = read_to_string(self.path.join("status")).map_err(|err| self.io_error(err))?;
+        // lines look like `Uid:\t1000\t1000\t1000\t1000` (real, effective, saved, filesystem)
+        let field = |name| {
+            status
+                .lines()
+                .find_map(|l| l.strip_prefix(name))
+                .and_then(|v| v.split_whitespace().next())
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0)
+        };
+        Ok((field("Uid:"), field("Gid:")))
+    }
+
+    /// the TIDs of all threads of the process, the main thread first
+    pub fn threads(&self) -> Result<Vec<u32>> {
+        let mut threads = read_dir(self.path.join("task"))
+            .map_err(|err| self.io_error(err))?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect::<Vec<u32>>();
+        threads.sort_by_key(|&tid| (tid != self.pid, tid));
+        Ok(threads)
+    }
+
+    /// the PIDs of the child processes of all threads
+    ///
+    /// Uses `/proc/PID/task/TID/children` and falls back to searching all processes
+    /// if the kernel doesn't provide it.
+    pub fn children(&self) -> Result<Vec<u32>> {
+        let mut children = Vec::new();
+        for tid in self.threads()? {
+            match read_to_string(self.path.join(format!("task/{tid}/children"))) {
+                Ok(pids) => children.extend(
+                    pids.split_whitespace()
+                        .filter_map(|p| p.parse::<u32>().ok()),
+                ),
+                Err(err) if err.kind() == io::ErrorKind::NotFound && tid == self.pid => {
+                    return Ok(Self::all()?
+                        .into_iter()
+                        .filter(|p| p.stat().is_ok_and(|stat| stat.ppid == self.pid))
+                        .map(|p| p.pid)
+                        .collect());
+                }
+                // the thread exited in the meantime
+                Err(_) => (),
+            }
+        }
+        children.sort();
+        Ok(children)
+    }
+
+    /// the process and all of its descendants, every process comes before its children
+    ///
+    /// Processes that exit while the tree is walked are left out.
+    pub fn tree(&self) -> Result<Vec<Self>> {
+        let mut tree = vec![Self::new(self.pid)?];
+        let mut stack = self.children()?;
+        stack.reverse();
+        while let Some(pid) = stack.pop() {
+            let Ok(process) = Self::new(pid) else {
+                continue;
+            };
+            stack.extend(process.children().unwrap_or_default().into_iter().rev());
+            tree.push(process);
+        }
+        Ok(tree)
+    }
+
+    /// all processes of the cgroup `cgroup` and of the cgroups below it
+    ///
+    /// `cgroup` is a directory below `/sys/fs/cgroup` or a path relative to it like
+    /// `/system.slice/nginx.service` as shown in `/proc/PID/cgroup`.
+    pub fn in_cgroup(cgroup: &Path) -> Result<Vec<Self>> {
+        let root = Path::new(CGROUP_ROOT);
+        let dir = if cgroup.starts_with(root) {
+            cgroup.to_path_buf()
+        } else {
+            root.join(cgroup.strip_prefix("/").unwrap_or(cgroup))
+        };
+        if !dir.join("cgroup.procs").exists() {
+            return Err(Error::InvalidArgument(format!(
+                "{} is not a cgroup",
+                dir.display()
+            )));
+        }
+        let mut pids = Vec::new();
+        let mut dirs = vec![dir];
+        while let Some(dir) = dirs.pop() {
+            let procs = read_to_string(dir.join("cgroup.procs"))?;
+            pids.extend(procs.lines().filter_map(|p| p.parse::<u32>().ok()));
+            for entry in read_dir(&dir)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    dirs.push(entry.path());
+                }
+            }
+        }
+        pids.sort();
+        pids.dedup();
+        Ok(pids
+            .into_iter()
+            .filter_map(|pid| Self::new(pid).ok())
+            .collect())
+    }
+
+    /// the auxiliary vector the kernel passed to the process
+    pub fn auxv(&self) -> Result<Vec<u8>> {
+        read(self.path.join("auxv")).map_err(|err| self.io_error(err))
+    }
+
+    /// stop the process until the returned `FrozenProcess` is dropped
+    pub fn freeze(&self, method: FreezeMethod) -> Result<FrozenProcess> {
+        FrozenProcess::new(self, method)
+    }
+
+    /// open the memory of the process for reading with the default backend
+    pub fn memory(&self) -> Result<ProcessMemory> {
+        self.memory_with(Backend::default())
+    }
+
+    /// open the memory of the process for reading with the given backend
+    pub fn memory_with(&self, backend: Backend) -> Result<ProcessMemory> {
+        let reader: Box<dyn MemoryReader> = match backend {
+            Backend::Auto => Box::new(AutoReader::new(self.pid, &self.path)),
+            Backend::VmReadv => Box::new(VmReadvReader::new(self.pid)),
+            Backend::ProcMem => {
+                Box::new(ProcMemReader::open(&self.path).map_err(|err| self.io_error(err))?)
+            }
+        };
+        Ok(ProcessMemory {
+            pid: self.pid,
+            reader,
+            // only used to skip unpopulated pages, so it's fine if it can't be opened
+            pagemap: Pagemap::open(&self.path).ok(),
+        })
+    }
+
+    /// convert an error that occurred while accessing `/proc/PID`
+    fn io_error(&self, err: io::Error) -> Error {
+        vanished_or(self.pid, err)
+    }
+}
+
+/// the parts of `/proc/PID/stat` that are of interest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stat {
+    pub state: char, // R, S, D, T, Z, ...
+    pub ppid: u32,
+    pub pgrp: i32,
+    pub session: i32,
+    pub flags: u32,
+    pub utime: u64, // in clock ticks
+    pub stime: u64,
+    pub cutime: u64,
+    pub cstime: u64,
+    pub nice: i64,
+    pub num_threads: u64,
+    pub start_time: u64, // clock ticks after boot
+}
+
+impl Stat {
+    fn parse(stat: &str) -> Option<Self> {
+        // the name in parentheses may contain spaces and parentheses itself
+        let (_, fields) = stat.rsplit_once(')')?;
+        let fields = fields.split_whitespace().collect::<Vec<&str>>();
+        Some(Self {
+            state: fields.first()?.chars().next()?,
+            ppid: stat_field(&fields, 4)?,
+            pgrp: stat_field(&fields, 5)?,
+            session: stat_field(&fields, 6)?,
+            flags: stat_field(&fields, 9)?,
+            utime: stat_field(&fields, 14)?,
+            stime: stat_field(&fields, 15)?,
+            cutime: stat_field(&fields, 16)?,
+            cstime: stat_field(&fields, 17)?,
+            nice: stat_field(&fields, 19)?,
+            num_threads: stat_field(&fields, 20)?,
+            start_time: stat_field(&fields, 22)?,
+        })
+    }
+}
+
+/// parse the `n`th field (1-based like in proc(5)) of `/proc/PID/stat`,
+/// `fields` starts after the name with the third field
+fn stat_field<T: FromStr>(fields: &[&str], n: usize) -> Option<T> {
+    fields.get(n - 3)?.parse().ok()
+}
+
+/// `Error::ProcessVanished` if the process with `pid` no longer exists, otherwise `err`
+pub(crate) fn vanished_or(pid: u32, err: io::Error) -> Error {
+    if !Path::new(&format!("/proc/{pid}")).exists() || err.raw_os_error() == Some(libc::ESRCH) {
+        Error::ProcessVanished(pid)
+    } else {
+        err.into()
+    }
+}
+
+/// read access to the memory of a process
+pub struct ProcessMemory {
+    pid: u32,
+    reader: Box<dyn MemoryReader>,
+    pub(crate) pagemap: Option<Pagemap>,
+}
+
+impl ProcessMemory {
+    /// read `buf.len()` bytes starting at the virtual address `address`
+    pub fn read_at(&mut self, address: u64, buf: &mut [u8]) -> Result<()> {
+        let read = self
+            .reader
+            .read_at(address, buf)
+            .map_err(|err| vanished_or(self.pid, err))?;
+        if read < buf.len() {
+            return Err(Error::ShortRead {
+                address,
+                expected: buf.len(),
+                read,
+            });
+        }
+        Ok(())
+    }
+
+    /// read up to `buf.len()` bytes starting at `address`, returns the number of bytes read
+    /// which is less than `buf.len()` if the end of a readable range was reached
+    pub fn read_partial(&mut self, address: u64, buf: &mut [u8]) -> Result<usize> {
+        self.reader
+            .read_at(address, buf)
+            .map_err(|err| vanished_or(self.pid, err))
+    }
+
+    /// read multiple ranges at once, returns the number of bytes read in total
+    ///
+    /// The ranges are filled in order, reading stops at the first range that could not be read completely.
+    pub fn read_vectored_at(&mut self, ranges: &mut [(u64, &mut [u8])]) -> Result<usize> {
+        self.reader
+            .read_vectored_at(ranges)
+            .map_err(|err| vanished_or(self.pid, err))
+    }
+}
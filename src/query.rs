@@ -0,0 +1,92 @@
+use std::{
+    fmt,
+    fs::canonicalize,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use crate::{Error, Process, Result};
+
+/// conditions a process has to match, all given conditions have to match
+///
+/// Useful to find a service whose PID changes on every restart.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessQuery {
+    pub name: Option<String>,   // as shown in `/proc/PID/comm`
+    pub cmdline: Option<Regex>, // matched against the arguments joined by spaces
+    pub exe: Option<PathBuf>,   // path of the executable
+    pub ppid: Option<u32>,      // PID of the parent process
+}
+
+impl ProcessQuery {
+    pub fn matches(&self, process: &Process) -> bool {
+        self.name
+            .as_ref()
+            .is_none_or(|name| process.comm().is_ok_and(|comm| comm == *name))
+            && self.cmdline.as_ref().is_none_or(|regex| {
+                process
+                    .cmdline()
+                    .is_ok_and(|cmdline| regex.is_match(&cmdline.join(" ")))
+            })
+            && self
+                .exe
+                .as_ref()
+                .is_none_or(|path| process.exe().is_ok_and(|exe| same_file(path, &exe)))
+            && self
+                .ppid
+                .is_none_or(|ppid| process.stat().is_ok_and(|stat| stat.ppid == ppid))
+    }
+
+    /// all running processes matching the query except for the current process, sorted by PID
+    pub fn find(&self) -> Result<Vec<Process>> {
+        let own_pid = std::process::id();
+        Ok(Process::all()?
+            .into_iter()
+            .filter(|p| p.pid() != own_pid && self.matches(p))
+            .collect())
+    }
+
+    /// the only process matching the query
+    ///
+    /// Fails with `Error::NoMatchingProcess` if there is none and `Error::AmbiguousProcess` if there are multiple.
+    pub fn find_one(&self) -> Result<Process> {
+        let mut processes = self.find()?;
+        match processes.len() {
+            0 => Err(Error::NoMatchingProcess(self.to_string())),
+            1 => Ok(processes.remove(0)),
+            _ => Err(Error::AmbiguousProcess(
+                self.to_string(),
+                processes.iter().map(Process::pid).collect(),
+            )),
+        }
+    }
+}
+
+/// describes the query like `name nginx, parent 1`
+impl fmt::Display for ProcessQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut conditions = Vec::new();
+        if let Some(name) = &self.name {
+            conditions.push(format!("name {name}"));
+        }
+        if let Some(regex) = &self.cmdline {
+            conditions.push(format!("cmdline /{regex}/"));
+        }
+        if let Some(exe) = &self.exe {
+            conditions.push(format!("exe {}", exe.display()));
+        }
+        if let Some(ppid) = self.ppid {
+            conditions.push(format!("parent {ppid}"));
+        }
+        if conditions.is_empty() {
+            return f.write_str("any process");
+        }
+        f.write_str(&conditions.join(", "))
+    }
+}
+
+/// true if `path` given by the user refers to the executable `exe` read from `/proc/PID/exe`
+fn same_file(path: &Path, exe: &Path) -> bool {
+    path == exe || canonicalize(path).is_ok_and(|path| path == exe)
+}
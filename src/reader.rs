@@ -0,0 +1,238 @@
+use std::{
+    fs::File,
+    io,
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::Error;
+
+/// maximum number of iovecs a single `process_vm_readv` call accepts
+const IOV_MAX: usize = 1024;
+
+/// a way to read the memory of another process
+pub trait MemoryReader {
+    /// read up to `buf.len()` bytes starting at the virtual address `address`,
+    /// returns the number of bytes read which is less than `buf.len()` if the end of a readable range was reached
+    fn read_at(&mut self, address: u64, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// read multiple ranges, returns the number of bytes read in total
+    ///
+    /// The ranges are filled in order, reading stops at the first range that could not be read completely.
+    fn read_vectored_at(&mut self, ranges: &mut [(u64, &mut [u8])]) -> io::Result<usize> {
+        let mut total = 0;
+        for (address, buf) in ranges.iter_mut() {
+            let read = match self.read_at(*address, buf) {
+                Ok(read) => read,
+                // the ranges before could be read, so this is the range that could not be read completely
+                Err(_) if total > 0 => break,
+                Err(err) => return Err(err),
+            };
+            total += read;
+            if read < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+}
+
+/// which `MemoryReader` should be used to read the memory of a process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// `process_vm_readv` with a fallback to `/proc/PID/mem`
+    #[default]
+    Auto,
+    VmReadv,
+    ProcMem,
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "vm-readv" => Ok(Self::VmReadv),
+            "proc-mem" => Ok(Self::ProcMem),
+            _ => Err(Error::InvalidArgument(format!("unknown backend '{s}'"))),
+        }
+    }
+}
+
+/// reads through the `/proc/PID/mem` file
+pub struct ProcMemReader {
+    file: File,
+}
+
+impl ProcMemReader {
+    /// `proc_path` is the `/proc/PID` directory of the process
+    pub fn open(proc_path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: File::open(proc_path.join("mem"))?,
+        })
+    }
+}
+
+impl MemoryReader for ProcMemReader {
+    fn read_at(&mut self, address: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.file.read_at(&mut buf[read..], address + read as u64) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                // an unreadable page after some bytes were read ends the readable range
+                Err(_) if read > 0 => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(read)
+    }
+}
+
+/// reads with the `process_vm_readv` syscall which can read many ranges at once
+pub struct VmReadvReader {
+    pid: libc::pid_t,
+}
+
+impl VmReadvReader {
+    pub fn new(pid: u32) -> Self {
+        Self {
+            pid: pid as libc::pid_t,
+        }
+    }
+}
+
+impl MemoryReader for VmReadvReader {
+    fn read_at(&mut self, address: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_vectored_at(&mut [(address, buf)])
+    }
+
+    fn read_vectored_at(&mut self, ranges: &mut [(u64, &mut [u8])]) -> io::Result<usize> {
+        let mut total = 0;
+        for chunk in ranges.chunks_mut(IOV_MAX) {
+            let expected = chunk.iter().map(|(_, buf)| buf.len()).sum::<usize>();
+            let local = chunk
+                .iter_mut()
+                .map(|(_, buf)| libc::iovec {
+                    iov_base: buf.as_mut_ptr().cast(),
+                    iov_len: buf.len(),
+                })
+                .collect::<Vec<_>>();
+            let remote = chunk
+                .iter()
+                .map(|(address, buf)| libc::iovec {
+                    iov_base: *address as usize as *mut libc::c_void,
+                    iov_len: buf.len(),
+                })
+                .collect::<Vec<_>>();
+            // SAFETY: the local iovecs point to the mutable buffers of `chunk` which outlive the call
+            let read = unsafe {
+                libc::process_vm_readv(
+                    self.pid,
+                    local.as_ptr(),
+                    local.len() as libc::c_ulong,
+                    remote.as_ptr(),
+                    remote.len() as libc::c_ulong,
+                    0,
+                )
+            };
+            if read < 0 {
+                let err = io::Error::last_os_error();
+                // EFAULT means the first range is not readable
+                if total > 0 && err.raw_os_error() == Some(libc::EFAULT) {
+                    break;
+                }
+                return Err(err);
+            }
+            total += read as usize;
+            if (read as usize) < expected {
+                break;
+            }
+        }
+        Ok(total)
+    }
+}
+
+/// uses `process_vm_readv` and falls back to `/proc/PID/mem`
+/// if the syscall is not available or a range can only be read through the file
+pub struct AutoReader {
+    vm_readv: Option<VmReadvReader>, // `None` after the syscall turned out to be unavailable
+    proc_mem: Option<ProcMemReader>, // opened on first use
+    proc_path: PathBuf,
+}
+
+impl AutoReader {
+    pub fn new(pid: u32, proc_path: &Path) -> Self {
+        Self {
+            vm_readv: Some(VmReadvReader::new(pid)),
+            proc_mem: None,
+            proc_path: proc_path.to_path_buf(),
+        }
+    }
+
+    fn proc_mem(&mut self) -> io::Result<&mut ProcMemReader> {
+        if self.proc_mem.is_none() {
+            self.proc_mem = Some(ProcMemReader::open(&self.proc_path)?);
+        }
+        Ok(self.proc_mem.as_mut().unwrap())
+    }
+}
+
+impl MemoryReader for AutoReader {
+    fn read_at(&mut self, address: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        if let Some(vm_readv) = &mut self.vm_readv {
+            match vm_readv.read_at(address, buf) {
+                Ok(n) => read = n,
+                Err(err) if matches!(err.raw_os_error(), Some(libc::ENOSYS | libc::EPERM)) => {
+                    self.vm_readv = None
+                }
+                Err(err) if err.raw_os_error() == Some(libc::EFAULT) => (),
+                Err(err) => return Err(err),
+            }
+        }
+        if read == buf.len() {
+            return Ok(read);
+        }
+        // /proc/PID/mem may be able to read pages process_vm_readv could not read
+        match self
+            .proc_mem()?
+            .read_at(address + read as u64, &mut buf[read..])
+        {
+            Ok(n) => Ok(read + n),
+            Err(_) if read > 0 => Ok(read),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn read_vectored_at(&mut self, ranges: &mut [(u64, &mut [u8])]) -> io::Result<usize> {
+        if let Some(vm_readv) = &mut self.vm_readv {
+            let expected = ranges.iter().map(|(_, buf)| buf.len()).sum::<usize>();
+            match vm_readv.read_vectored_at(ranges) {
+                Ok(n) if n == expected => return Ok(n),
+                Err(err) if matches!(err.raw_os_error(), Some(libc::ENOSYS | libc::EPERM)) => {
+                    self.vm_readv = None
+                }
+                Ok(_) | Err(_) => (),
+            }
+        }
+        // read range by range so the fallback of `read_at` is used
+        let mut total = 0;
+        for (address, buf) in ranges.iter_mut() {
+            let read = match self.read_at(*address, buf) {
+                Ok(read) => read,
+                // the ranges before could be read, so this is the range that could not be read completely
+                Err(_) if total > 0 => break,
+                Err(err) => return Err(err),
+            };
+            total += read;
+            if read < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+}
@@ -0,0 +1,156 @@
+use std::{fmt, path::Path};
+
+use crate::{
+    diff::compare, manifest::Hole, Backend, ChangedRange, Error, Filter, Manifest, Memory, Process,
+    Result, Snapshot, VirtMemoryPage,
+};
+
+/// number of bytes written at once
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// why a region of a dump was not restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skipped {
+    Gone,        // there is no mapping at its address anymore
+    Moved,       // its address range is now used by mappings with a different range or pathname
+    NotWritable, // the mapping is not writable anymore
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Gone => "mapping is gone",
+            Self::Moved => "mapping moved",
+            Self::NotWritable => "mapping is not writable anymore",
+        })
+    }
+}
+
+/// outcome of restoring a region of a dump
+#[derive(Debug)]
+pub struct RestoredRegion {
+    pub page: VirtMemoryPage, // the region as it was dumped
+    pub skipped: Option<Skipped>,
+    pub changes: Vec<ChangedRange>, // ranges that differ from the dump and were written
+    pub unreadable: bool, // part of the region could not be read from the dump or the process
+    pub error: Option<Error>, // the first error that occurred while writing
+}
+
+impl RestoredRegion {
+    /// number of bytes that differ from the dump
+    pub fn changed_bytes(&self) -> u64 {
+        self.changes.iter().map(|c| c.end - c.start).sum()
+    }
+}
+
+/// write the writable regions of a dump matching `filter` back into `process`
+///
+/// Only regions whose mapping still has the same address range and pathname are restored,
+/// others are skipped. Only bytes that differ from the dump are written, ranges that could not be
+/// read when the dump was created are left alone.
+/// With `dry_run` the differences are determined but nothing is written.
+pub fn restore(
+    process: &Process,
+    dump: &Path,
+    filter: &Filter,
+    dry_run: bool,
+) -> Result<Vec<RestoredRegion>> {
+    let manifest = Manifest::of_dump(dump)?;
+    let mut old = Snapshot::open(dump)?;
+    let mut live = Snapshot::live(process, Backend::Auto)?;
+    let mut writer = if dry_run {
+        None
+    } else {
+        Some(process.writer()?)
+    };
+
+    let mut restored = Vec::new();
+    for region in &manifest.regions {
+        let page = region.page()?;
+        if !page.is_writable() || !filter.matches(&page) {
+            continue;
+        }
+        let mut result = RestoredRegion {
+            page,
+            skipped: None,
+            changes: Vec::new(),
+            unreadable: false,
+            error: None,
+        };
+        result.skipped = skip_reason(&result.page, &live.pages);
+        if result.skipped.is_none() {
+            let (changes, unreadable) = compare(&mut old, &mut live, &result.page)?;
+            result.changes = without_holes(changes, &region.holes);
+            result.unreadable = unreadable;
+            if let Some(writer) = &mut writer {
+                let mut buf = vec![0; CHUNK_SIZE];
+                'changes: for change in &result.changes {
+                    let mut address = change.start;
+                    while address < change.end {
+                        let len = CHUNK_SIZE.min((change.end - address) as usize);
+                        let written = old.read_partial(address, &mut buf[..len]).and_then(|read| {
+                            if read == 0 {
+                                return Err(Error::ShortRead {
+                                    address,
+                                    expected: len,
+                                    read,
+                                });
+                            }
+                            writer.write_all_at(address, &buf[..read]).map(|()| read)
+                        });
+                        match written {
+                            Ok(read) => address += read as u64,
+                            Err(err) if err.is_recoverable() => {
+                                result.error = Some(err);
+                                break 'changes;
+                            }
+                            Err(err) => return Err(err),
+                        }
+                    }
+                }
+            }
+        }
+        restored.push(result);
+    }
+    Ok(restored)
+}
+
+/// why `page` can't be restored into a process with the current mappings `pages`, if it can't
+fn skip_reason(page: &VirtMemoryPage, pages: &[VirtMemoryPage]) -> Option<Skipped> {
+    match pages
+        .iter()
+        .find(|p| p.from == page.from && p.to == page.to && p.file_path == page.file_path)
+    {
+        Some(current) if current.is_writable() => None,
+        Some(_) => Some(Skipped::NotWritable),
+        None if pages.iter().any(|p| p.from < page.to && page.from < p.to) => Some(Skipped::Moved),
+        None => Some(Skipped::Gone),
+    }
+}
+
+/// `changes` without the sorted address ranges `holes`
+fn without_holes(changes: Vec<ChangedRange>, holes: &[Hole]) -> Vec<ChangedRange> {
+    let mut result = Vec::new();
+    for change in changes {
+        let mut start = change.start;
+        for hole in holes
+            .iter()
+            .filter(|h| h.start < change.end && change.start < h.end)
+        {
+            if start < hole.start {
+                result.push(ChangedRange {
+                    start,
+                    end: hole.start,
+                });
+            }
+            start = start.max(hole.end);
+        }
+        if start < change.end {
+            result.push(ChangedRange {
+                start,
+                end: change.end,
+            });
+        }
+    }
+    result
+}
@@ -0,0 +1,346 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    manifest::hex, search, Error, Pattern, Process, ProcessMemory, Result, VirtMemoryPage,
+};
+
+/// number of candidates read with a single `read_vectored_at` call
+const BATCH_SIZE: usize = 1024;
+
+/// type of the values to scan for, numbers are in native byte order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    String, // UTF-8 encoded, without terminating NUL
+}
+
+impl FromStr for ValueType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "string" => Self::String,
+            _ => return Err(Error::InvalidArgument(format!("unknown value type '{s}'"))),
+        })
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::String => "string",
+        })
+    }
+}
+
+impl ValueType {
+    /// size of a value in bytes, `None` for strings
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Self::U8 | Self::I8 => Some(1),
+            Self::U16 | Self::I16 => Some(2),
+            Self::U32 | Self::I32 | Self::F32 => Some(4),
+            Self::U64 | Self::I64 | Self::F64 => Some(8),
+            Self::String => None,
+        }
+    }
+
+    /// the in-memory representation of the value given as text
+    pub fn encode(&self, value: &str) -> Result<Vec<u8>> {
+        let invalid = || Error::InvalidArgument(format!("invalid {self} value '{value}'"));
+        macro_rules! encode {
+            ($t:ty) => {
+                value
+                    .parse::<$t>()
+                    .map_err(|_| invalid())?
+                    .to_ne_bytes()
+                    .to_vec()
+            };
+        }
+        Ok(match self {
+            Self::U8 => encode!(u8),
+            Self::U16 => encode!(u16),
+            Self::U32 => encode!(u32),
+            Self::U64 => encode!(u64),
+            Self::I8 => encode!(i8),
+            Self::I16 => encode!(i16),
+            Self::I32 => encode!(i32),
+            Self::I64 => encode!(i64),
+            Self::F32 => encode!(f32),
+            Self::F64 => encode!(f64),
+            Self::String if value.is_empty() => return Err(invalid()),
+            Self::String => value.as_bytes().to_vec(),
+        })
+    }
+
+    /// the value as text
+    pub fn format(&self, bytes: &[u8]) -> String {
+        macro_rules! format {
+            ($t:ty) => {
+                <$t>::from_ne_bytes(bytes.try_into().unwrap()).to_string()
+            };
+        }
+        match self {
+            Self::U8 => format!(u8),
+            Self::U16 => format!(u16),
+            Self::U32 => format!(u32),
+            Self::U64 => format!(u64),
+            Self::I8 => format!(i8),
+            Self::I16 => format!(i16),
+            Self::I32 => format!(i32),
+            Self::I64 => format!(i64),
+            Self::F32 => format!(f32),
+            Self::F64 => format!(f64),
+            Self::String => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    }
+
+    /// compare two values, `None` for strings and NaN
+    fn compare(&self, a: &[u8], b: &[u8]) -> Option<Ordering> {
+        macro_rules! compare {
+            ($t:ty) => {
+                <$t>::from_ne_bytes(a.try_into().unwrap())
+                    .partial_cmp(&<$t>::from_ne_bytes(b.try_into().unwrap()))
+            };
+        }
+        match self {
+            Self::U8 => compare!(u8),
+            Self::U16 => compare!(u16),
+            Self::U32 => compare!(u32),
+            Self::U64 => compare!(u64),
+            Self::I8 => compare!(i8),
+            Self::I16 => compare!(i16),
+            Self::I32 => compare!(i32),
+            Self::I64 => compare!(i64),
+            Self::F32 => compare!(f32),
+            Self::F64 => compare!(f64),
+            Self::String => None,
+        }
+    }
+}
+
+/// how the value of a candidate has to relate to its previous value to stay a candidate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Equals(Vec<u8>),
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+impl Condition {
+    fn matches(&self, value_type: ValueType, old: &[u8], new: &[u8]) -> bool {
+        match self {
+            Self::Equals(value) => new == value,
+            Self::Changed => old != new,
+            Self::Unchanged => old == new,
+            Self::Increased => value_type.compare(new, old) == Some(Ordering::Greater),
+            Self::Decreased => value_type.compare(new, old) == Some(Ordering::Less),
+        }
+    }
+}
+
+/// an address that may hold the value being looked for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Candidate {
+    #[serde(with = "hex")]
+    pub address: u64,
+    #[serde(with = "hex_bytes")]
+    pub value: Vec<u8>, // value at the last scan
+}
+
+/// the state of an iterative scan, can be saved to continue it later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scan {
+    pub pid: u32,
+    pub start_time: u64, // to detect that the PID was reused by another process
+    pub value_type: ValueType,
+    pub candidates: Vec<Candidate>,
+}
+
+impl Scan {
+    /// find all addresses in `pages` holding `value`, numbers have to be aligned to their size unless `unaligned`
+    pub fn first(
+        process: &Process,
+        memory: &mut ProcessMemory,
+        pages: &[VirtMemoryPage],
+        value_type: ValueType,
+        value: &[u8],
+        unaligned: bool,
+    ) -> Result<Self> {
+        let align = if unaligned {
+            1
+        } else {
+            value_type.size().unwrap_or(1) as u64
+        };
+        let pattern = Pattern::Bytes(value.iter().copied().map(Some).collect());
+        let candidates = search(memory, pages, &pattern)?
+            .into_iter()
+            .filter(|m| m.address % align == 0)
+            .map(|m| Candidate {
+                address: m.address,
+                value: value.to_vec(),
+            })
+            .collect();
+        Ok(Self {
+            pid: process.pid(),
+            start_time: process.stat()?.start_time,
+            value_type,
+            candidates,
+        })
+    }
+
+    /// fails if `process` is not the process this scan was started on
+    pub fn check_process(&self, process: &Process) -> Result<()> {
+        if process.pid() != self.pid || process.stat()?.start_time != self.start_time {
+            return Err(Error::ProcessVanished(self.pid));
+        }
+        Ok(())
+    }
+
+    /// read the current values of all candidates and keep those matching `condition`,
+    /// candidates that can't be read anymore are dropped
+    pub fn narrow(&mut self, memory: &mut ProcessMemory, condition: &Condition) -> Result<()> {
+        if self.value_type == ValueType::String
+            && matches!(condition, Condition::Increased | Condition::Decreased)
+        {
+            return Err(Error::InvalidArgument(
+                "strings can't increase or decrease".to_string(),
+            ));
+        }
+
+        let mut kept = Vec::new();
+        let mut start = 0;
+        while start < self.candidates.len() {
+            let batch = &self.candidates[start..(start + BATCH_SIZE).min(self.candidates.len())];
+            let mut values = batch
+                .iter()
+                .map(|c| vec![0; c.value.len()])
+                .collect::<Vec<Vec<u8>>>();
+            let mut ranges = batch
+                .iter()
+                .zip(values.iter_mut())
+                .map(|(c, buf)| (c.address, buf.as_mut_slice()))
+                .collect::<Vec<_>>();
+            let read = match memory.read_vectored_at(&mut ranges) {
+                Ok(read) => read,
+                Err(err) if err.is_recoverable() => 0,
+                Err(err) => return Err(err),
+            };
+
+            // the ranges are filled in order, so count the candidates that were read completely
+            let mut remaining = read;
+            let mut complete = 0;
+            for candidate in batch {
+                if remaining < candidate.value.len() {
+                    break;
+                }
+                remaining -= candidate.value.len();
+                complete += 1;
+            }
+            for (candidate, value) in batch.iter().zip(values).take(complete) {
+                if condition.matches(self.value_type, &candidate.value, &value) {
+                    kept.push(Candidate {
+                        address: candidate.address,
+                        value,
+                    });
+                }
+            }
+            // skip the candidate that could not be read
+            start += if complete < batch.len() {
+                complete + 1
+            } else {
+                complete
+            };
+        }
+        self.candidates = kept;
+        Ok(())
+    }
+
+    /// read a scan file, fails if a value doesn't have the size of the value type
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        let scan: Self = serde_json::from_reader(file)
+            .map_err(|err| Error::InvalidArgument(format!("invalid scan file {path:?}: {err}")))?;
+        for candidate in &scan.candidates {
+            let valid = match scan.value_type.size() {
+                Some(size) => candidate.value.len() == size,
+                None => !candidate.value.is_empty(),
+            };
+            if !valid {
+                return Err(Error::InvalidArgument(format!(
+                    "invalid scan file {path:?}: the value of {:#x} is not a valid {}",
+                    candidate.address, scan.value_type
+                )));
+            }
+        }
+        Ok(scan)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path).map_err(Error::Output)?);
+        serde_json::to_writer(&mut file, self).map_err(|err| Error::Output(err.into()))?;
+        file.write_all(b"\n").map_err(Error::Output)?;
+        file.flush().map_err(Error::Output)
+    }
+}
+
+/// (de)serialize bytes as hex string
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.iter().map(|b| format!("{b:02x}")).collect::<String>())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.len() % 2 != 0 {
+            return Err(D::Error::custom("odd number of hex digits"));
+        }
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(D::Error::custom))
+            .collect()
+    }
+}
@@ -0,0 +1,203 @@
+use regex::bytes::Regex;
+
+use crate::{page_size, Error, Memory, Result, VirtMemoryPage};
+
+/// number of bytes searched at once
+const CHUNK_SIZE: usize = 1 << 20;
+/// number of bytes a regex match may extend into the next chunk
+const REGEX_OVERLAP: usize = 4096;
+
+/// something to search for in the memory of a process
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// a byte sequence, `None` matches any byte
+    Bytes(Vec<Option<u8>>),
+    Regex(Regex),
+}
+
+impl Pattern {
+    /// parse hex bytes separated by whitespace, `??` matches any byte, e.g. `48 8B ?? ?? 89`
+    pub fn hex(pattern: &str) -> Result<Self> {
+        let invalid = || Error::InvalidArgument(format!("invalid hex pattern '{pattern}'"));
+        let bytes = pattern
+            .split_whitespace()
+            .map(|byte| match byte {
+                "??" | "?" => Ok(None),
+                _ if byte.len() == 2 => u8::from_str_radix(byte, 16)
+                    .map(Some)
+                    .map_err(|_| invalid()),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if bytes.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self::Bytes(bytes))
+    }
+
+    /// the UTF-8 (or ASCII) encoding of `s`
+    pub fn string(s: &str) -> Self {
+        Self::Bytes(s.bytes().map(Some).collect())
+    }
+
+    /// the UTF-16 little endian encoding of `s`
+    pub fn utf16(s: &str) -> Self {
+        Self::Bytes(
+            s.encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .map(Some)
+                .collect(),
+        )
+    }
+
+    /// a regular expression matched against raw bytes, see `regex::bytes`
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(Self::Regex)
+            .map_err(|err| Error::InvalidArgument(err.to_string()))
+    }
+
+    /// number of bytes a match can extend past the start of the next chunk
+    fn overlap(&self) -> usize {
+        match self {
+            Self::Bytes(bytes) => bytes.len() - 1,
+            Self::Regex(_) => REGEX_OVERLAP,
+        }
+    }
+
+    /// call `on_match` with the offset and length of all matches in `haystack` starting before `end`
+    fn find(&self, haystack: &[u8], end: usize, mut on_match: impl FnMut(usize, usize)) {
+        match self {
+            Self::Bytes(bytes) => {
+                if haystack.len() < bytes.len() {
+                    return;
+                }
+                let last_start = (haystack.len() - bytes.len()).min(end.saturating_sub(1));
+                for start in 0..=last_start {
+                    let matches = bytes
+                        .iter()
+                        .zip(&haystack[start..])
+                        .all(|(b, h)| b.is_none_or(|b| b == *h));
+                    if matches && start < end {
+                        on_match(start, bytes.len());
+                    }
+                }
+            }
+            Self::Regex(regex) => {
+                for m in regex.find_iter(haystack) {
+                    if m.start() >= end {
+                        break;
+                    }
+                    on_match(m.start(), m.len());
+                }
+            }
+        }
+    }
+}
+
+/// a location in the memory of a process where a pattern matched
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub address: u64, // absolute virtual address
+    pub len: usize,
+    pub page: VirtMemoryPage, // the page containing the match
+    pub offset: u64,          // offset of the match inside of `page`
+}
+
+/// search `pattern` in `pages` without copying them, unreadable parts of pages are skipped
+///
+/// A regex match can only be found if it is at most 4 KiB long.
+pub fn search<M: Memory + ?Sized>(
+    memory: &mut M,
+    pages: &[VirtMemoryPage],
+    pattern: &Pattern,
+) -> Result<Vec<Match>> {
+    let overlap = pattern.overlap();
+    let mut buf = vec![0; CHUNK_SIZE + overlap];
+    let mut matches = Vec::new();
+    for page in pages {
+        let mut address = page.from;
+        // a regex match running into the overlap is found again by the next chunk, maybe shorter
+        let mut reported_to = page.from;
+        while address < page.to {
+            let len = buf.len().min((page.to - address) as usize);
+            let read = match memory.read_partial(address, &mut buf[..len]) {
+                Ok(read) => read,
+                Err(err) if err.is_recoverable() => 0,
+                Err(err) => return Err(err),
+            };
+            if read == 0 {
+                // skip the unreadable memory page
+                address = (address + 1).next_multiple_of(page_size());
+                continue;
+            }
+            // matches starting in the overlap are found with the next chunk
+            let end = if read == len && address + (read as u64) < page.to {
+                read.saturating_sub(overlap).max(1)
+            } else {
+                read
+            };
+            let is_regex = matches!(pattern, Pattern::Regex(_));
+            pattern.find(&buf[..read], end, |start, len| {
+                let match_address = address + start as u64;
+                if is_regex && match_address < reported_to {
+                    return;
+                }
+                reported_to = match_address + len as u64;
+                matches.push(Match {
+                    address: match_address,
+                    len,
+                    page: page.clone(),
+                    offset: match_address - page.from,
+                })
+            });
+            address += end as u64;
+        }
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// memory starting at address 0
+    struct Buffer(Vec<u8>);
+
+    impl Memory for Buffer {
+        fn read_partial(&mut self, address: u64, buf: &mut [u8]) -> Result<usize> {
+            let data = &self.0[address as usize..];
+            let len = buf.len().min(data.len());
+            buf[..len].copy_from_slice(&data[..len]);
+            Ok(len)
+        }
+    }
+
+    /// 100 `a` surrounded by zeros, crossing the end of the first chunk
+    fn straddling_chunks() -> (Buffer, Vec<VirtMemoryPage>) {
+        let mut data = vec![0; 2 * CHUNK_SIZE];
+        let start = CHUNK_SIZE - 10;
+        data[start..start + 100].fill(b'a');
+        let page =
+            VirtMemoryPage::from_line(&format!("0-{:x} rw-p 0 00:00 0", data.len())).unwrap();
+        (Buffer(data), vec![page])
+    }
+
+    #[test]
+    fn regex_match_at_chunk_boundary_is_reported_once() {
+        let (mut memory, pages) = straddling_chunks();
+        let pattern = Pattern::regex("[a-z]+").unwrap();
+        let matches = search(&mut memory, &pages, &pattern).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].address, CHUNK_SIZE as u64 - 10);
+        assert_eq!(matches[0].len, 100);
+    }
+
+    #[test]
+    fn bytes_match_at_chunk_boundary() {
+        let (mut memory, pages) = straddling_chunks();
+        let matches = search(&mut memory, &pages, &Pattern::string("aaa")).unwrap();
+        assert_eq!(matches.len(), 98);
+        assert!(matches.windows(2).all(|m| m[1].address == m[0].address + 1));
+    }
+}
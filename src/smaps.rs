@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ParseError, VirtMemoryPage};
+
+/// memory usage of a mapping from `/proc/PID/smaps`, sizes are in bytes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Smaps {
+    pub rss: u64,
+    pub pss: u64,
+    pub shared_clean: u64,
+    pub shared_dirty: u64,
+    pub private_clean: u64,
+    pub private_dirty: u64,
+    pub swap: u64,
+    pub anon_huge_pages: u64,
+    pub vm_flags: Vec<String>, // two letter flags like `rd`, `wr` or `dd`, see proc(5)
+}
+
+/// a size of `Smaps` that can be used by filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmapsField {
+    Rss,
+    Pss,
+    SharedClean,
+    SharedDirty,
+    PrivateClean,
+    PrivateDirty,
+    Swap,
+    AnonHugePages,
+}
+
+impl SmapsField {
+    /// the field with a name like `private-dirty`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "rss" => Self::Rss,
+            "pss" => Self::Pss,
+            "shared-clean" => Self::SharedClean,
+            "shared-dirty" => Self::SharedDirty,
+            "private-clean" => Self::PrivateClean,
+            "private-dirty" => Self::PrivateDirty,
+            "swap" => Self::Swap,
+            "anon-huge-pages" => Self::AnonHugePages,
+            _ => return None,
+        })
+    }
+}
+
+impl Smaps {
+    pub fn get(&self, field: SmapsField) -> u64 {
+        match field {
+            SmapsField::Rss => self.rss,
+            SmapsField::Pss => self.pss,
+            SmapsField::SharedClean => self.shared_clean,
+            SmapsField::SharedDirty => self.shared_dirty,
+            SmapsField::PrivateClean => self.private_clean,
+            SmapsField::PrivateDirty => self.private_dirty,
+            SmapsField::Swap => self.swap,
+            SmapsField::AnonHugePages => self.anon_huge_pages,
+        }
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.vm_flags.iter().any(|f| f == flag)
+    }
+
+    /// parse a `Key: value` line of `/proc/PID/smaps`, unknown keys are ignored
+    fn parse_field(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if key == "VmFlags" {
+            self.vm_flags = value.split_whitespace().map(str::to_string).collect();
+            return Ok(());
+        }
+        let field = match key {
+            "Rss" => &mut self.rss,
+            "Pss" => &mut self.pss,
+            "Shared_Clean" => &mut self.shared_clean,
+            "Shared_Dirty" => &mut self.shared_dirty,
+            "Private_Clean" => &mut self.private_clean,
+            "Private_Dirty" => &mut self.private_dirty,
+            "Swap" => &mut self.swap,
+            "AnonHugePages" => &mut self.anon_huge_pages,
+            _ => return Ok(()),
+        };
+        let kb = value
+            .strip_suffix(" kB")
+            .and_then(|kb| kb.trim().parse::<u64>().ok())
+            .ok_or_else(|| format!("invalid {key} '{value}'"))?;
+        *field = kb * 1024;
+        Ok(())
+    }
+}
+
+impl VirtMemoryPage {
+    /// parse the whole content of `/proc/PID/smaps`, every page has `stats`
+    pub fn parse_smaps(smaps: &str) -> Result<Vec<Self>, ParseError> {
+        let mut pages: Vec<Self> = Vec::new();
+        for (i, line) in smaps.lines().enumerate() {
+            let with_line = |mut err: ParseError| {
+                err.line = i + 1;
+                err
+            };
+            let field = line
+                .split_once(':')
+                .filter(|(key, _)| !key.contains(char::is_whitespace));
+            match (field, pages.last_mut()) {
+                (Some((key, value)), Some(page)) => page
+                    .stats
+                    .get_or_insert_with(Smaps::default)
+                    .parse_field(key, value.trim())
+                    .map_err(|reason| with_line(ParseError::new(line, reason)))?,
+                (Some(_), None) => {
+                    return Err(with_line(ParseError::new(
+                        line,
+                        "field before the first mapping",
+                    )))
+                }
+                (None, _) if line.is_empty() => (),
+                (None, _) => {
+                    let mut page = Self::from_line(line).map_err(with_line)?;
+                    page.stats = Some(Smaps::default());
+                    pages.push(page);
+                }
+            }
+        }
+        Ok(pages)
+    }
+}
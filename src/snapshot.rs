@@ -0,0 +1,286 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    manifest::core_manifest_path, tar, Backend, Compression, Error, Manifest, Mapping,
+    MemoryReader, Process, ProcessMemory, Result, VirtMemoryPage, MANIFEST_FILE_NAME,
+};
+
+/// the memory of a process at some point: a dump or the live process
+pub struct Snapshot {
+    pub pages: Vec<VirtMemoryPage>, // the readable pages, for a dump only the dumped ones
+    pub mappings: Vec<Mapping>, // all mappings of the process, including those that weren't dumped
+    source: Source,
+}
+
+enum Source {
+    Live(ProcessMemory),
+    Dump(DumpReader),
+}
+
+impl Snapshot {
+    /// the current memory of a running process
+    pub fn live(process: &Process, backend: Backend) -> Result<Self> {
+        let pages = process.maps()?;
+        Ok(Self {
+            mappings: pages.iter().map(Mapping::from).collect(),
+            pages,
+            source: Source::Live(process.memory_with(backend)?),
+        })
+    }
+
+    /// a dump directory, core file or tar archive created by this crate, the manifest is used to find the pages
+    pub fn open(path: &Path) -> Result<Self> {
+        let reader = DumpReader::open(path)?;
+        Ok(Self {
+            pages: reader.pages.iter().map(|(page, _)| page.clone()).collect(),
+            mappings: reader.mappings.clone(),
+            source: Source::Dump(reader),
+        })
+    }
+}
+
+/// memory that can be read at any address: a live process or a dump
+pub trait Memory {
+    /// read up to `buf.len()` bytes starting at `address`, returns the number of bytes read
+    /// which is less than `buf.len()` if the end of a readable range was reached
+    fn read_partial(&mut self, address: u64, buf: &mut [u8]) -> Result<usize>;
+}
+
+impl Memory for Snapshot {
+    fn read_partial(&mut self, address: u64, buf: &mut [u8]) -> Result<usize> {
+        match &mut self.source {
+            Source::Live(memory) => memory.read_partial(address, buf),
+            Source::Dump(reader) => Ok(reader.read_at(address, buf)?),
+        }
+    }
+}
+
+impl Memory for ProcessMemory {
+    fn read_partial(&mut self, address: u64, buf: &mut [u8]) -> Result<usize> {
+        ProcessMemory::read_partial(self, address, buf)
+    }
+}
+
+/// where the content of a dumped page is stored
+enum Location {
+    File(PathBuf),          // a file of a dump directory containing only this page
+    Offset { offset: u64 }, // offset of the page in a core file or tar archive
+    Missing,                // the page content was not dumped
+}
+
+/// reads the memory stored in a dump directory, core file or tar archive,
+/// compressed files are decompressed on the fly
+pub struct DumpReader {
+    pages: Vec<(VirtMemoryPage, Location)>, // sorted by address
+    mappings: Vec<Mapping>,                 // all mappings recorded in the manifest
+    compression: Compression,
+    archive: Option<DumpFile>,       // the core file or tar archive
+    open: Option<(usize, DumpFile)>, // the last used file of a dump directory and the index of its page
+}
+
+impl DumpReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let not_a_dump = || Error::InvalidArgument(format!("{path:?} is not a dump"));
+        let mut pages = Vec::new();
+        let mut archive = None;
+        let compression;
+        let manifest;
+        if path.is_dir() {
+            manifest = Manifest::read(&path.join(MANIFEST_FILE_NAME))?;
+            compression = manifest.compression;
+            for region in &manifest.regions {
+                let location = match &region.file {
+                    Some(file) => Location::File(path.join(file)),
+                    None => Location::Missing,
+                };
+                pages.push((region.page()?, location));
+            }
+        } else if core_manifest_path(path).exists() {
+            manifest = Manifest::read(&core_manifest_path(path))?;
+            compression = manifest.compression;
+            let mut core = DumpFile::open(path, compression)?;
+            let loads = core_loads(&mut core)
+                .map_err(|_| Error::InvalidArgument(format!("{path:?} is not a core file")))?;
+            for region in &manifest.regions {
+                let location = match loads
+                    .iter()
+                    .find(|(address, _, _)| *address == region.start)
+                {
+                    Some(&(_, offset, size)) if size > 0 => Location::Offset { offset },
+                    _ => Location::Missing,
+                };
+                pages.push((region.page()?, location));
+            }
+            archive = Some(core);
+        } else {
+            // a tar archive containing the manifest
+            compression = Compression::detect(path)?;
+            let mut tar = DumpFile::open(path, compression)?;
+            let members = tar::members(&mut tar).map_err(|_| not_a_dump())?;
+            manifest = Manifest::from_tar(&mut tar, &members)?.ok_or_else(not_a_dump)?;
+            for region in &manifest.regions {
+                let member = region
+                    .file
+                    .as_ref()
+                    .and_then(|file| members.iter().find(|m| Path::new(&m.name) == file));
+                let location = match member {
+                    Some(member) => Location::Offset {
+                        offset: member.offset,
+                    },
+                    None => Location::Missing,
+                };
+                pages.push((region.page()?, location));
+            }
+            archive = Some(tar);
+        }
+        pages.sort_by_key(|(page, _)| page.from);
+        // older manifests don't record the mappings that weren't dumped
+        let mappings = if manifest.mappings.is_empty() {
+            pages.iter().map(|(page, _)| Mapping::from(page)).collect()
+        } else {
+            manifest.mappings
+        };
+        Ok(Self {
+            pages,
+            mappings,
+            compression,
+            archive,
+            open: None,
+        })
+    }
+}
+
+impl MemoryReader for DumpReader {
+    fn read_at(&mut self, address: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let index = self.pages.partition_point(|(page, _)| page.to <= address);
+        let Some((page, location)) = self.pages.get(index).filter(|(p, _)| p.from <= address)
+        else {
+            return Err(io::Error::from_raw_os_error(libc::EFAULT));
+        };
+        let len = buf.len().min((page.to - address) as usize);
+        let offset = address - page.from;
+        match location {
+            Location::File(path) => {
+                if self.open.as_ref().is_none_or(|(i, _)| *i != index) {
+                    self.open = Some((index, DumpFile::open(path, self.compression)?));
+                }
+                let (_, file) = self.open.as_mut().unwrap();
+                file.read_at(&mut buf[..len], offset)
+            }
+            Location::Offset { offset: start } => match &mut self.archive {
+                Some(archive) => archive.read_at(&mut buf[..len], start + offset),
+                None => Ok(0),
+            },
+            Location::Missing => Err(io::Error::from_raw_os_error(libc::EIO)),
+        }
+    }
+}
+
+/// a file of a dump that can be read at any offset
+pub(crate) enum DumpFile {
+    Plain(File),
+    /// decompressed from the start, reading before `position` starts over
+    Compressed {
+        path: PathBuf,
+        compression: Compression,
+        decoder: Box<dyn Read>,
+        position: u64, // offset of the next decompressed byte
+    },
+}
+
+impl DumpFile {
+    pub(crate) fn open(path: &Path, compression: Compression) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(match compression {
+            Compression::None => Self::Plain(file),
+            _ => Self::Compressed {
+                path: path.to_path_buf(),
+                compression,
+                decoder: compression.decoder(file)?,
+                position: 0,
+            },
+        })
+    }
+
+    /// read into `buf` until it is full or the end of the file was reached
+    pub(crate) fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let mut read = 0;
+        match self {
+            Self::Plain(file) => {
+                while read < buf.len() {
+                    match file.read_at(&mut buf[read..], offset + read as u64) {
+                        Ok(0) => break,
+                        Ok(n) => read += n,
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+            Self::Compressed {
+                path,
+                compression,
+                decoder,
+                position,
+            } => {
+                if offset < *position {
+                    *decoder = compression.decoder(File::open(&*path)?)?;
+                    *position = 0;
+                }
+                *position += io::copy(&mut decoder.take(offset - *position), &mut io::sink())?;
+                if *position < offset {
+                    return Ok(0);
+                }
+                while read < buf.len() {
+                    match decoder.read(&mut buf[read..]) {
+                        Ok(0) => break,
+                        Ok(n) => read += n,
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                        Err(err) => return Err(err),
+                    }
+                }
+                *position += read as u64;
+            }
+        }
+        Ok(read)
+    }
+
+    pub(crate) fn read_exact_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if self.read_at(buf, offset)? < buf.len() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+}
+
+/// (virtual address, file offset, file size) of all `PT_LOAD` segments of an ELF core file
+fn core_loads(core: &mut DumpFile) -> io::Result<Vec<(u64, u64, u64)>> {
+    let invalid = || io::Error::from(io::ErrorKind::InvalidData);
+    let mut header = [0; 64];
+    core.read_exact_at(&mut header, 0)?;
+    if header[..4] != *b"\x7fELF" || header[4] != 2 {
+        return Err(invalid());
+    }
+    let u16_at = |buf: &[u8], offset: usize| u16::from_ne_bytes([buf[offset], buf[offset + 1]]);
+    let u64_at =
+        |buf: &[u8], offset: usize| u64::from_ne_bytes(buf[offset..offset + 8].try_into().unwrap());
+    let phoff = u64_at(&header, 32);
+    let mut phnum = u16_at(&header, 56) as u64;
+    if phnum == 0xffff {
+        // PN_XNUM, the real number is sh_info of the first section header
+        let mut section = [0; 4];
+        core.read_exact_at(&mut section, u64_at(&header, 40) + 44)?;
+        phnum = u32::from_ne_bytes(section) as u64;
+    }
+    let mut headers = vec![0; phnum as usize * 56];
+    core.read_exact_at(&mut headers, phoff)?;
+    Ok(headers
+        .chunks_exact(56)
+        .filter(|phdr| u32::from_ne_bytes(phdr[..4].try_into().unwrap()) == 1) // PT_LOAD
+        .map(|phdr| (u64_at(phdr, 16), u64_at(phdr, 8), u64_at(phdr, 32)))
+        .collect())
+}
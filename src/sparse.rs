@@ -0,0 +1,99 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+};
+
+use crate::page_size;
+
+/// destination of copied memory that may skip over zeros
+pub trait Sink: Write {
+    /// write `n` zero bytes
+    fn write_zeros(&mut self, n: u64) -> io::Result<()> {
+        io::copy(&mut io::repeat(0).take(n), self)?;
+        Ok(())
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    fn write_zeros(&mut self, n: u64) -> io::Result<()> {
+        (**self).write_zeros(n)
+    }
+}
+
+impl<S: Sink> Sink for BufWriter<S> {
+    fn write_zeros(&mut self, n: u64) -> io::Result<()> {
+        self.flush()?;
+        self.get_mut().write_zeros(n)
+    }
+}
+
+impl Sink for Vec<u8> {}
+
+/// a sink that writes zeros to any writer, e.g. a pipe
+pub struct ZeroFill<W: Write>(pub W);
+
+impl<W: Write> Write for ZeroFill<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<W: Write> Sink for ZeroFill<W> {}
+
+/// a file that seeks over zero pages instead of writing them, so they don't take up disk space
+pub struct SparseFile {
+    file: File,
+    position: u64,
+    len: u64,
+}
+
+impl SparseFile {
+    /// `file` has to be empty
+    pub fn new(file: File) -> Self {
+        Self {
+            file,
+            position: 0,
+            len: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+}
+
+impl Write for SparseFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for block in buf.chunks(page_size() as usize) {
+            if block.iter().all(|&b| b == 0) {
+                self.write_zeros(block.len() as u64)?;
+            } else {
+                self.file.write_all(block)?;
+                self.position += block.len() as u64;
+                self.len = self.len.max(self.position);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Sink for SparseFile {
+    fn write_zeros(&mut self, n: u64) -> io::Result<()> {
+        self.position += n;
+        self.file.seek(SeekFrom::Start(self.position))?;
+        if self.position > self.len {
+            // extend the file, so it has the right size even if it ends with zeros
+            self.file.set_len(self.position)?;
+            self.len = self.position;
+        }
+        Ok(())
+    }
+}
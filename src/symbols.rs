@@ -0,0 +1,183 @@
+use std::{fs::File, io, os::unix::fs::FileExt};
+
+use crate::{page_size, Process, Result, VirtMemoryPage};
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+const PT_LOAD: u32 = 1;
+const SYMBOL_SIZE: usize = 24; // size of an Elf64_Sym
+const ELF_HEADER_SIZE: usize = 64;
+const SECTION_HEADER_SIZE: u64 = 64;
+const PROGRAM_HEADER_SIZE: usize = 56;
+
+/// a defined symbol of an ELF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String, // without the version, e.g. `malloc` instead of `malloc@@GLIBC_2.2.5`
+    pub value: u64,   // virtual address in the file, the load bias has to be added
+    pub size: u64,
+}
+
+/// the defined symbols of the 64 bit ELF file `elf` from `.symtab` and `.dynsym`
+///
+/// Only the headers and the symbol and string tables are read, not the whole file.
+pub fn elf_symbols(elf: &File) -> io::Result<Vec<Symbol>> {
+    let header = elf_header(elf)?;
+    let shoff = u64_at(&header, 0x28).unwrap();
+    let shentsize = u16_at(&header, 0x3a).unwrap() as u64;
+    let shnum = u16_at(&header, 0x3c).unwrap() as u64;
+    if shentsize < SECTION_HEADER_SIZE {
+        return Err(invalid_elf());
+    }
+    let headers = read_range(elf, shoff, shnum.checked_mul(shentsize))?;
+    // at least `SECTION_HEADER_SIZE` bytes, so the fields can be read without checking the length
+    let section_header = |index: u64| {
+        (index < shnum)
+            .then(|| &headers[(index * shentsize) as usize..((index + 1) * shentsize) as usize])
+    };
+    // the content of a section
+    let section = |index: u64| {
+        let header = section_header(index).ok_or_else(invalid_elf)?;
+        read_range(elf, u64_at(header, 0x18).unwrap(), u64_at(header, 0x20))
+    };
+
+    let mut symbols = Vec::new();
+    for index in 0..shnum {
+        let header = section_header(index).ok_or_else(invalid_elf)?;
+        let sh_type = u32_at(header, 4).unwrap();
+        if sh_type != SHT_SYMTAB && sh_type != SHT_DYNSYM {
+            continue;
+        }
+        let table = section(index)?;
+        // sh_link is the index of the string table of the symbol names
+        let strings = section(u32_at(header, 0x28).unwrap() as u64)?;
+        for symbol in table.chunks_exact(SYMBOL_SIZE) {
+            let name = u32_at(symbol, 0).unwrap() as usize;
+            let shndx = u16_at(symbol, 6).unwrap();
+            if name == 0 || shndx == 0 || name >= strings.len() {
+                continue; // unnamed or undefined
+            }
+            let name = &strings[name..];
+            let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+            symbols.push(Symbol {
+                name: String::from_utf8_lossy(name)
+                    .split('@')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                value: u64_at(symbol, 8).unwrap(),
+                size: u64_at(symbol, 16).unwrap(),
+            });
+        }
+    }
+    Ok(symbols)
+}
+
+/// the lowest virtual address of the `PT_LOAD` segments of the ELF file `elf` rounded down to a page
+fn elf_base(elf: &File) -> io::Result<u64> {
+    let header = elf_header(elf)?;
+    let phoff = u64_at(&header, 0x20).unwrap();
+    let phentsize = u16_at(&header, 0x36).unwrap() as usize;
+    let phnum = u16_at(&header, 0x38).unwrap() as u64;
+    if phentsize < PROGRAM_HEADER_SIZE {
+        return Err(invalid_elf());
+    }
+    let headers = read_range(elf, phoff, phnum.checked_mul(phentsize as u64))?;
+    headers
+        .chunks_exact(phentsize)
+        .filter(|header| u32_at(header, 0) == Some(PT_LOAD))
+        .filter_map(|header| u64_at(header, 0x10))
+        .min()
+        .map(|vaddr| vaddr & !(page_size() - 1))
+        .ok_or_else(invalid_elf)
+}
+
+/// the ELF header of `elf`, fails if it's not a 64 bit ELF file
+fn elf_header(elf: &File) -> io::Result<[u8; ELF_HEADER_SIZE]> {
+    let mut header = [0; ELF_HEADER_SIZE];
+    elf.read_exact_at(&mut header, 0)
+        .map_err(|_| invalid_elf())?;
+    if header[..5] != *b"\x7fELF\x02" {
+        return Err(invalid_elf());
+    }
+    Ok(header)
+}
+
+/// read `len` bytes at `offset` of `elf`, fails if the range is not inside of the file
+fn read_range(elf: &File, offset: u64, len: Option<u64>) -> io::Result<Vec<u8>> {
+    let len = len.ok_or_else(invalid_elf)?;
+    let file_len = elf.metadata()?.len();
+    if offset.checked_add(len).is_none_or(|end| end > file_len) {
+        return Err(invalid_elf());
+    }
+    let mut buf = vec![0; len as usize];
+    elf.read_exact_at(&mut buf, offset)?;
+    Ok(buf)
+}
+
+fn invalid_elf() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid 64 bit ELF file")
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(
+        buf.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        buf.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(
+        buf.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+impl Process {
+    /// the address of the symbol `name` in the first ELF file mapped by the process that defines it
+    ///
+    /// The files are read through `/proc/PID/root`, so this also works for processes in containers.
+    /// Returns `None` if no mapped file defines the symbol.
+    pub fn symbol_address(&self, name: &str) -> Result<Option<u64>> {
+        let pages = self.maps()?;
+        let mut searched = Vec::new();
+        for page in &pages {
+            if !page.file_path.starts_with('/')
+                || page.is_deleted()
+                || searched.contains(&&page.file_path)
+            {
+                continue;
+            }
+            searched.push(&page.file_path);
+            let Ok(elf) = File::open(self.proc_path().join("root").join(&page.file_path[1..]))
+            else {
+                continue;
+            };
+            // files that aren't ELF files, like locale-archive, fail after reading the first bytes
+            let Ok(symbols) = elf_symbols(&elf) else {
+                continue;
+            };
+            let (Some(symbol), Ok(base), Some(load)) = (
+                symbols.iter().find(|s| s.name == name),
+                elf_base(&elf),
+                load_address(&pages, &page.file_path),
+            ) else {
+                continue;
+            };
+            return Ok(Some(load - base + symbol.value));
+        }
+        Ok(None)
+    }
+}
+
+/// the address the file `path` is mapped to: the start of its mapping with offset 0
+fn load_address(pages: &[VirtMemoryPage], path: &str) -> Option<u64> {
+    pages
+        .iter()
+        .find(|page| page.file_path == path && page.offset == 0)
+        .map(|page| page.from)
+}
@@ -0,0 +1,179 @@
+use std::io::{self, Write};
+
+use crate::{snapshot::DumpFile, Sink};
+
+/// size of a tar header and the unit the content of members is padded to
+const BLOCK_SIZE: u64 = 512;
+/// largest size that fits into the octal size field, larger sizes are stored in base-256
+const MAX_OCTAL_SIZE: u64 = 0o77777777777;
+
+/// writes a ustar archive member by member without seeking, so it can be streamed e.g. to stdout
+pub struct TarWriter<S: Sink> {
+    out: S,
+    mtime: u64,     // modification time of all members in seconds since the unix epoch
+    remaining: u64, // bytes of the current member that still have to be written
+    padding: u64,   // zeros to write after the content of the current member
+}
+
+impl<S: Sink> TarWriter<S> {
+    pub fn new(out: S, mtime: u64) -> Self {
+        Self {
+            out,
+            mtime,
+            remaining: 0,
+            padding: 0,
+        }
+    }
+
+    /// start a regular file member of `size` bytes, the content is written through `Write` and `Sink`
+    ///
+    /// Exactly `size` bytes have to be written before the next member is started.
+    pub fn start_member(&mut self, name: &str, size: u64) -> io::Result<()> {
+        self.end_member()?;
+        self.out.write_all(&header(name, size, self.mtime)?)?;
+        self.remaining = size;
+        self.padding = size.next_multiple_of(BLOCK_SIZE) - size;
+        Ok(())
+    }
+
+    /// add a regular file member containing `data`
+    pub fn append(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        self.start_member(name, data.len() as u64)?;
+        self.write_all(data)
+    }
+
+    /// write the end of the archive and return the underlying writer
+    pub fn finish(mut self) -> io::Result<S> {
+        self.end_member()?;
+        self.out.write_zeros(2 * BLOCK_SIZE)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// pad the content of the current member to a full block
+    fn end_member(&mut self) -> io::Result<()> {
+        if self.remaining > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "tar member is shorter than its size",
+            ));
+        }
+        self.out.write_zeros(self.padding)?;
+        self.padding = 0;
+        Ok(())
+    }
+}
+
+impl<S: Sink> Write for TarWriter<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() as u64 > self.remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "tar member is longer than its size",
+            ));
+        }
+        let written = self.out.write(buf)?;
+        self.remaining -= written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+impl<S: Sink> Sink for TarWriter<S> {
+    fn write_zeros(&mut self, n: u64) -> io::Result<()> {
+        if n > self.remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "tar member is longer than its size",
+            ));
+        }
+        self.out.write_zeros(n)?;
+        self.remaining -= n;
+        Ok(())
+    }
+}
+
+/// the ustar header of a regular file, `name` must be at most 100 bytes long
+fn header(name: &str, size: u64, mtime: u64) -> io::Result<[u8; BLOCK_SIZE as usize]> {
+    if name.len() > 100 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("tar member name '{name}' is too long"),
+        ));
+    }
+    let mut header = [0; BLOCK_SIZE as usize];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    octal(&mut header[100..108], 0o644); // mode
+    octal(&mut header[108..116], 0); // uid
+    octal(&mut header[116..124], 0); // gid
+    if size <= MAX_OCTAL_SIZE {
+        octal(&mut header[124..136], size);
+    } else {
+        // GNU base-256 encoding: the highest bit is set and the size is stored big endian
+        header[124] = 0x80;
+        header[128..136].copy_from_slice(&size.to_be_bytes());
+    }
+    octal(&mut header[136..148], mtime);
+    header[156] = b'0'; // typeflag: regular file
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    // the checksum is calculated with spaces in the checksum field
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|&b| b as u64).sum::<u64>();
+    octal(&mut header[148..155], checksum);
+    Ok(header)
+}
+
+/// write `value` as NUL terminated octal number filling `field`
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{value:0width$o}", width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[digits.len()] = 0;
+}
+
+/// a regular file inside of a tar archive
+#[derive(Debug, Clone)]
+pub(crate) struct Member {
+    pub name: String,
+    pub offset: u64, // offset of the content in the archive
+    pub size: u64,
+}
+
+/// list the regular files of a tar archive
+pub(crate) fn members(archive: &mut DumpFile) -> io::Result<Vec<Member>> {
+    let invalid = |reason| io::Error::new(io::ErrorKind::InvalidData, reason);
+    let mut members = Vec::new();
+    let mut offset = 0;
+    let mut header = [0; BLOCK_SIZE as usize];
+    loop {
+        archive.read_exact_at(&mut header, offset)?;
+        if header.iter().all(|&b| b == 0) {
+            return Ok(members);
+        }
+        if header[257..262] != *b"ustar" {
+            return Err(invalid("not a tar archive"));
+        }
+        let size = if header[124] & 0x80 != 0 {
+            u64::from_be_bytes(header[128..136].try_into().unwrap())
+        } else {
+            parse_octal(&header[124..136]).ok_or_else(|| invalid("invalid tar member size"))?
+        };
+        let name_len = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+        if matches!(header[156], b'0' | 0) {
+            members.push(Member {
+                name: String::from_utf8_lossy(&header[..name_len]).into_owned(),
+                offset: offset + BLOCK_SIZE,
+                size,
+            });
+        }
+        offset += BLOCK_SIZE + size.next_multiple_of(BLOCK_SIZE);
+    }
+}
+
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let digits = std::str::from_utf8(field).ok()?;
+    u64::from_str_radix(digits.trim_matches(|c: char| c == '\0' || c == ' '), 8).ok()
+}
@@ -0,0 +1,181 @@
+use std::{fmt, io};
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    coredump::{ELF_NGREG, NT_PRSTATUS},
+    freeze::seize,
+    Error, Process, Result, Stat, VirtMemoryPage,
+};
+
+/// names of the registers in the order of `struct user_regs_struct`
+#[cfg(target_arch = "x86_64")]
+const REGISTER_NAMES: [&str; ELF_NGREG] = [
+    "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx", "rdx", "rsi",
+    "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base", "gs_base", "ds", "es", "fs",
+    "gs",
+];
+#[cfg(target_arch = "x86_64")]
+const STACK_POINTER: usize = 19;
+#[cfg(target_arch = "x86_64")]
+const INSTRUCTION_POINTER: usize = 16;
+#[cfg(target_arch = "aarch64")]
+const REGISTER_NAMES: [&str; ELF_NGREG] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "x29", "x30", "sp", "pc", "pstate",
+];
+#[cfg(target_arch = "aarch64")]
+const STACK_POINTER: usize = 31;
+#[cfg(target_arch = "aarch64")]
+const INSTRUCTION_POINTER: usize = 32;
+
+/// a thread of a process at the time it was captured
+#[derive(Debug)]
+pub struct Thread {
+    pub tid: u32,
+    pub name: String, // as shown in /proc/PID/task/TID/comm
+    pub stat: Stat,
+    pub registers: Option<Registers>, // `None` if they could not be read
+    pub error: Option<Error>,         // why the registers could not be read
+}
+
+impl Thread {
+    /// the mapping of `pages` containing the stack pointer of the thread
+    pub fn stack<'a>(&self, pages: &'a [VirtMemoryPage]) -> Option<&'a VirtMemoryPage> {
+        let sp = self.registers.as_ref()?.stack_pointer();
+        pages.iter().find(|p| p.from <= sp && sp < p.to)
+    }
+}
+
+/// the general purpose registers of a thread like in the `NT_PRSTATUS` note of a core file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registers(pub [u64; ELF_NGREG]);
+
+impl Registers {
+    pub fn stack_pointer(&self) -> u64 {
+        self.0[STACK_POINTER]
+    }
+
+    pub fn instruction_pointer(&self) -> u64 {
+        self.0[INSTRUCTION_POINTER]
+    }
+
+    /// name and value of every register
+    pub fn named(&self) -> impl Iterator<Item = (&'static str, u64)> + '_ {
+        REGISTER_NAMES.iter().copied().zip(self.0.iter().copied())
+    }
+}
+
+/// (de)serialized as an object of register names and hex strings like `{"rip": "0x401000", ...}`
+impl Serialize for Registers {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(ELF_NGREG))?;
+        for (name, value) in self.named() {
+            map.serialize_entry(name, &format!("{value:#x}"))?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Registers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct RegistersVisitor;
+
+        impl<'de> Visitor<'de> for RegistersVisitor {
+            type Value = Registers;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object of register names and hex values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Registers, A::Error> {
+                let mut registers = [0; ELF_NGREG];
+                while let Some((name, value)) = map.next_entry::<String, String>()? {
+                    let i = REGISTER_NAMES
+                        .iter()
+                        .position(|&n| n == name)
+                        .ok_or_else(|| de::Error::unknown_field(&name, &REGISTER_NAMES))?;
+                    registers[i] = u64::from_str_radix(value.trim_start_matches("0x"), 16)
+                        .map_err(de::Error::custom)?;
+                }
+                Ok(Registers(registers))
+            }
+        }
+
+        deserializer.deserialize_map(RegistersVisitor)
+    }
+}
+
+impl Process {
+    /// name, state and registers of every thread, the main thread first
+    ///
+    /// The registers are read with `PTRACE_GETREGSET`. Threads that are not attached already,
+    /// e.g. by `FreezeMethod::Ptrace`, are attached and interrupted while their registers are read.
+    /// If the registers of a thread can't be read the error is recorded instead of returned.
+    /// Threads that exit while they are captured are left out.
+    pub fn capture_threads(&self) -> Result<Vec<Thread>> {
+        let mut threads = Vec::new();
+        for tid in self.threads()? {
+            let (Ok(stat), Ok(name)) = (self.thread_stat(tid), self.thread_comm(tid)) else {
+                continue; // the thread exited in the meantime
+            };
+            let (registers, error) = match registers(tid) {
+                Ok(registers) => (Some(registers), None),
+                Err(err) if err.raw_os_error() == Some(libc::ESRCH) => continue,
+                Err(err) => (None, Some(err.into())),
+            };
+            threads.push(Thread {
+                tid,
+                name,
+                stat,
+                registers,
+                error,
+            });
+        }
+        Ok(threads)
+    }
+}
+
+/// read the registers of `tid`, attaching to it if it's not traced by us yet
+fn registers(tid: u32) -> io::Result<Registers> {
+    match get_regset(tid) {
+        // ESRCH if the thread is not attached or not stopped
+        Err(err) if err.raw_os_error() == Some(libc::ESRCH) => {
+            seize(tid)?;
+            let registers = get_regset(tid);
+            // SAFETY: PTRACE_DETACH doesn't access memory of our process
+            unsafe { libc::ptrace(libc::PTRACE_DETACH, tid as libc::pid_t, 0, 0) };
+            registers
+        }
+        result => result,
+    }
+}
+
+fn get_regset(tid: u32) -> io::Result<Registers> {
+    let mut registers = [0u64; ELF_NGREG];
+    let mut iov = libc::iovec {
+        iov_base: registers.as_mut_ptr().cast(),
+        iov_len: std::mem::size_of_val(&registers),
+    };
+    // SAFETY: the iovec points to a buffer of `iov_len` bytes, the kernel shortens `iov_len` if it writes less
+    let result = unsafe {
+        libc::ptrace(
+            libc::PTRACE_GETREGSET,
+            tid as libc::pid_t,
+            NT_PRSTATUS as usize,
+            &mut iov as *mut libc::iovec,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Registers(registers))
+}
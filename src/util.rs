@@ -0,0 +1,45 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    io::{self, Read, Write},
+};
+
+/// group a Vec<V> by keys given by fn(key: &V) -> K to HashMap<K, Vec<V>>
+pub fn group_by<K: Hash + Eq, V>(list: Vec<V>, key_fn: fn(key: &V) -> K) -> HashMap<K, Vec<V>> {
+    let mut map: HashMap<K, Vec<V>> = HashMap::new();
+    for element in list {
+        let key = key_fn(&element);
+        if let Some(value) = map.get_mut(&key) {
+            value.push(element);
+        } else {
+            map.insert(key, vec![element]);
+        }
+    }
+    map
+}
+
+/// copy up to `n` bytes from `from` to `to` chunkwise,
+/// returns the number of bytes copied which is less than `n` if `from` reached its end
+pub fn ncopy<R: Read, W: Write>(mut from: R, mut to: W, n: usize) -> io::Result<usize> {
+    let mut already_read = 0;
+    let mut buf = vec![0; 256];
+    while already_read < n {
+        // don't read full 256 bytes if less than 256 are left
+        let len = buf.len().min(n - already_read);
+        let read = match from.read(&mut buf[..len]) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        already_read += read;
+        to.write_all(&buf[..read])?;
+    }
+    Ok(already_read)
+}
+
+/// size of a memory page of the system in bytes
+pub fn page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as u64 }
+}
@@ -0,0 +1,73 @@
+use std::{fs::File, fs::OpenOptions, os::unix::fs::FileExt, path::Path};
+
+use crate::{process::vanished_or, Error, Process, Result, VirtMemoryPage};
+
+/// writes to the memory of a process through `/proc/PID/mem`
+///
+/// Like a debugger it can also write to pages that are not writable, e.g. to patch code.
+pub struct MemoryWriter {
+    pid: u32,
+    file: File,
+}
+
+impl MemoryWriter {
+    /// `proc_path` is the `/proc/PID` directory of the process
+    pub(crate) fn open(pid: u32, proc_path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(proc_path.join("mem"))
+            .map_err(|err| vanished_or(pid, err))?;
+        Ok(Self { pid, file })
+    }
+
+    /// write `bytes` at `address`, returns the bytes that were overwritten
+    pub fn write_at(&mut self, address: u64, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut old = vec![0; bytes.len()];
+        self.file
+            .read_exact_at(&mut old, address)
+            .map_err(|err| vanished_or(self.pid, err))?;
+        self.write_all_at(address, bytes)?;
+        Ok(old)
+    }
+
+    /// write `bytes` at `address` without reading what was there before
+    pub fn write_all_at(&mut self, address: u64, bytes: &[u8]) -> Result<()> {
+        self.file
+            .write_all_at(bytes, address)
+            .map_err(|err| vanished_or(self.pid, err))
+    }
+}
+
+/// fails with `Error::NotMapped` if the `len` bytes at `address` are not inside of `pages`
+/// and with `Error::NotWritable` if some of them are not writable, unless `force` is true
+pub fn check_writable(pages: &[VirtMemoryPage], address: u64, len: u64, force: bool) -> Result<()> {
+    let end = address.checked_add(len).ok_or(Error::NotMapped(address))?;
+    let mut position = address;
+    while position < end {
+        let page = pages
+            .iter()
+            .find(|p| p.from <= position && position < p.to)
+            .ok_or(Error::NotMapped(position))?;
+        if !force && !page.is_writable() {
+            return Err(Error::NotWritable(position));
+        }
+        position = page.to;
+    }
+    Ok(())
+}
+
+impl Process {
+    /// open the memory of the process for writing
+    pub fn writer(&self) -> Result<MemoryWriter> {
+        MemoryWriter::open(self.pid(), self.proc_path())
+    }
+
+    /// write `bytes` at `address` and return the bytes that were overwritten
+    ///
+    /// The bytes have to be inside of writable pages unless `force` is true.
+    pub fn write_memory(&self, address: u64, bytes: &[u8], force: bool) -> Result<Vec<u8>> {
+        check_writable(&self.maps()?, address, bytes.len() as u64, force)?;
+        self.writer()?.write_at(address, bytes)
+    }
+}